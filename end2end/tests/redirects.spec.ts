@@ -0,0 +1,40 @@
+import { test, expect } from "@playwright/test";
+
+const site = "http://localhost:3000";
+
+test("logged out visitors are sent to log in with a 302", async ({ request }) => {
+  for (const [path, next] of [
+    ["/settings", "%2Fsettings"],
+    ["/editor", "%2Feditor"],
+    ["/?feed=your", "%2F%3Ffeed%3Dyour"],
+  ]) {
+    const response = await request.get(site + path, { maxRedirects: 0 });
+
+    expect(response.status()).toBe(302);
+    expect(response.headers()["location"]).toBe(`/login?next=${next}`);
+  }
+});
+
+test("old article slugs answer with a 301 to the current one", async ({ request }) => {
+  const name = `redirect${Date.now()}`;
+  const registered = await request.post(`${site}/api/users`, {
+    data: { user: { username: name, email: `${name}@example.com`, password: "password1234" } },
+  });
+  const headers = { Authorization: `Token ${(await registered.json()).user.token}` };
+  const created = await request.post(`${site}/api/articles`, {
+    headers,
+    data: { article: { title: `Before ${name}`, description: "-", body: "-" } },
+  });
+  const before = (await created.json()).article.slug;
+  const renamed = await request.put(`${site}/api/articles/${before}`, {
+    headers,
+    data: { article: { title: `After ${name}` } },
+  });
+  const after = (await renamed.json()).article.slug;
+
+  const response = await request.get(`${site}/article/${before}`, { maxRedirects: 0 });
+
+  expect(after).not.toBe(before);
+  expect(response.status()).toBe(301);
+  expect(response.headers()["location"]).toBe(`/article/${after}`);
+});
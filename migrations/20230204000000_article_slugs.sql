@@ -0,0 +1,8 @@
+-- Slugs an article went by before it was renamed, so old links keep working.
+CREATE TABLE IF NOT EXISTS article_slugs (
+    slug TEXT PRIMARY KEY,
+    article_id INTEGER NOT NULL REFERENCES articles (id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX IF NOT EXISTS article_slugs_article_id ON article_slugs (article_id);
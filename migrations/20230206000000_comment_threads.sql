@@ -0,0 +1,9 @@
+-- Replies point at the comment they answer. `depth` is 0 for top level
+-- comments and bounds how deep a thread can go.
+ALTER TABLE comments ADD COLUMN parent_id INTEGER REFERENCES comments (id) ON DELETE CASCADE;
+ALTER TABLE comments ADD COLUMN depth INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE comments ADD COLUMN edited_at TEXT;
+-- Deleted comments stay behind without their body so replies keep their place.
+ALTER TABLE comments ADD COLUMN deleted_at TEXT;
+
+CREATE INDEX IF NOT EXISTS comments_parent_id ON comments (parent_id);
@@ -0,0 +1,10 @@
+-- Sessions name their user by id rather than by email, so they can't pass to
+-- whoever signs up with an address its owner gave up. Existing sessions only
+-- know the email and are ended.
+DELETE FROM sessions;
+
+DROP INDEX IF EXISTS sessions_user_email;
+ALTER TABLE sessions DROP COLUMN user_email;
+ALTER TABLE sessions ADD COLUMN user_id INTEGER REFERENCES users (id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS sessions_user_id ON sessions (user_id);
@@ -0,0 +1,3 @@
+-- API tokens carry the version they were issued under, and stop working
+-- once it's bumped, as when a user logs out all their devices.
+ALTER TABLE users ADD COLUMN token_version INTEGER NOT NULL DEFAULT 0;
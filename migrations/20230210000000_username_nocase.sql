@@ -0,0 +1,3 @@
+-- Usernames are unique regardless of case, like emails, so "Alice" and
+-- "alice" can't both sign up.
+CREATE UNIQUE INDEX IF NOT EXISTS users_username_nocase ON users (username COLLATE NOCASE);
@@ -0,0 +1,777 @@
+//! The RealWorld ("Conduit") JSON API, for clients that don't go through the
+//! Leptos server functions. Handlers here only translate between the spec's
+//! JSON envelopes and the same domain code the server functions use.
+
+use crate::{
+    app::{CurrentUser, LoginForm, RegisterForm, SettingsForm},
+    article::{self, Article, ArticleFilter, ArticleForm},
+    comment::{self, Comment, CommentError, CommentForm},
+    jwt::Tokens,
+    user::{self, Profile, UserRow},
+    validations::FieldError,
+};
+use actix_web::{
+    delete,
+    error::{
+        ErrorForbidden, ErrorInternalServerError, ErrorNotFound, ErrorUnauthorized,
+        ErrorUnprocessableEntity,
+    },
+    get,
+    http::header,
+    post, put, web, Error, HttpResponse,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+
+/// Mounts every API route. Call this before the catch-all server function
+/// route, since both live under `/api`.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(login)
+        .service(register)
+        .service(current_user)
+        .service(update_user)
+        .service(get_profile)
+        .service(follow_user)
+        .service(unfollow_user)
+        .service(list_articles)
+        // before `get_article`, which would take "feed" for a slug. No article
+        // gets that slug, see `article::unique_slug`.
+        .service(feed_articles)
+        .service(create_article)
+        .service(get_article)
+        .service(update_article)
+        .service(delete_article)
+        .service(favorite_article)
+        .service(unfavorite_article)
+        .service(list_comments)
+        .service(add_comment)
+        .service(edit_comment)
+        .service(delete_comment)
+        .service(get_tags);
+}
+
+#[derive(Deserialize)]
+struct UserBody<T> {
+    user: T,
+}
+
+#[derive(Deserialize)]
+struct LoginUser {
+    email: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+struct NewUser {
+    username: String,
+    email: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UserChanges {
+    email: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    image: Option<String>,
+    bio: Option<String>,
+    /// Not in the spec, but needed to change `email` or `password`.
+    current_password: Option<String>,
+}
+
+#[derive(Serialize)]
+struct UserJson {
+    email: String,
+    token: String,
+    username: String,
+    bio: Option<String>,
+    image: Option<String>,
+}
+
+#[derive(Serialize)]
+struct UserResponse {
+    user: UserJson,
+}
+
+impl UserResponse {
+    /// Wraps `row` with a freshly issued token, as every user response in the
+    /// spec carries one.
+    fn new(tokens: &Tokens, row: UserRow) -> Result<Self, Error> {
+        let token = tokens
+            .issue(row.id, row.token_version)
+            .map_err(ErrorInternalServerError)?;
+        Ok(UserResponse {
+            user: UserJson {
+                email: row.email,
+                token,
+                username: row.username,
+                bio: row.bio,
+                image: row.image,
+            },
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct ProfileResponse {
+    profile: Profile,
+}
+
+#[derive(Deserialize)]
+struct ArticleBody<T> {
+    article: T,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NewArticle {
+    title: String,
+    description: String,
+    body: String,
+    #[serde(default)]
+    tag_list: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ArticleChanges {
+    title: Option<String>,
+    description: Option<String>,
+    body: Option<String>,
+    tag_list: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct ArticleResponse {
+    article: Article,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArticlesResponse {
+    articles: Vec<Article>,
+    articles_count: i64,
+}
+
+#[derive(Deserialize)]
+struct ArticlesQuery {
+    tag: Option<String>,
+    author: Option<String>,
+    favorited: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct PageQuery {
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct CommentBody<T> {
+    comment: T,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NewComment {
+    body: String,
+    /// Set to reply to another comment.
+    parent_id: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct CommentChanges {
+    body: String,
+}
+
+#[derive(Serialize)]
+struct CommentResponse {
+    comment: Comment,
+}
+
+#[derive(Serialize)]
+struct CommentsResponse {
+    comments: Vec<Comment>,
+}
+
+#[derive(Serialize)]
+struct TagsResponse {
+    tags: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ErrorsResponse {
+    errors: BTreeMap<&'static str, Vec<String>>,
+}
+
+/// Builds the spec's `422 {"errors": {"field": ["message", ...]}}` response,
+/// leaving out fields without errors.
+fn unprocessable<'a>(
+    errors: impl IntoIterator<Item = (&'static str, &'a [FieldError])>,
+) -> HttpResponse {
+    let errors = errors
+        .into_iter()
+        .filter(|(_, errors)| !errors.is_empty())
+        .map(|(name, errors)| (name, errors.iter().map(|e| e.to_string()).collect()))
+        .collect();
+    HttpResponse::UnprocessableEntity().json(ErrorsResponse { errors })
+}
+
+/// Resolves the authenticated caller to their full user record.
+async fn user_row(pool: &SqlitePool, current_user: &CurrentUser) -> Result<UserRow, Error> {
+    user::find_by_id(pool, current_user.id)
+        .await
+        .map_err(ErrorInternalServerError)?
+        .ok_or_else(|| ErrorUnauthorized("unauthorized"))
+}
+
+/// Looks up the user `username`, or responds with a 404.
+async fn find_user(pool: &SqlitePool, username: &str) -> Result<UserRow, Error> {
+    user::find_by_username(pool, username)
+        .await
+        .map_err(ErrorInternalServerError)?
+        .ok_or_else(|| ErrorNotFound("profile not found"))
+}
+
+/// The id of the caller, for responses that depend on who is asking.
+async fn viewer_id(
+    pool: &SqlitePool,
+    current_user: &Option<CurrentUser>,
+) -> Result<Option<i64>, Error> {
+    match current_user {
+        Some(current_user) => Ok(Some(user_row(pool, current_user).await?.id)),
+        None => Ok(None),
+    }
+}
+
+/// Fetches the article `slug` for changing it, which only its author may do.
+async fn own_article(
+    pool: &SqlitePool,
+    slug: &str,
+    author: &UserRow,
+) -> Result<article::ArticleRow, Error> {
+    let article = find_article(pool, slug).await?;
+    if article.author_id != author.id {
+        return Err(ErrorForbidden("only the author can change this article"));
+    }
+    Ok(article)
+}
+
+/// Looks up the article `slug`, or responds with a 404.
+async fn find_article(pool: &SqlitePool, slug: &str) -> Result<article::ArticleRow, Error> {
+    article::find_by_slug(pool, slug)
+        .await
+        .map_err(ErrorInternalServerError)?
+        .ok_or_else(|| ErrorNotFound("article not found"))
+}
+
+/// One page of the articles matching `filter`, counting all of them.
+async fn articles_page(
+    pool: &SqlitePool,
+    filter: &ArticleFilter,
+    viewer_id: Option<i64>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<HttpResponse, Error> {
+    let (limit, offset) = article::clamp_page(limit, offset);
+    let articles: Vec<Article> = article::list(pool, filter, viewer_id, limit, offset)
+        .await
+        .map_err(ErrorInternalServerError)?
+        .into_iter()
+        .map(Article::from)
+        .collect();
+    let articles_count = article::count(pool, filter)
+        .await
+        .map_err(ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(ArticlesResponse {
+        articles,
+        articles_count,
+    }))
+}
+
+/// Fetches the comment `id` on `article_id` for changing it, which only its
+/// author may do.
+async fn own_comment(
+    pool: &SqlitePool,
+    article_id: i64,
+    id: i64,
+    author: &UserRow,
+) -> Result<comment::CommentRow, Error> {
+    let comment = comment::find(pool, id)
+        .await
+        .map_err(ErrorInternalServerError)?
+        .filter(|comment| comment.article_id == article_id && comment.deleted_at.is_none())
+        .ok_or_else(|| ErrorNotFound("comment not found"))?;
+    if comment.author_id != author.id {
+        return Err(ErrorForbidden("only the author can change this comment"));
+    }
+    Ok(comment)
+}
+
+/// Answers a comment that couldn't be saved with a status for its reason.
+fn comment_error(e: CommentError) -> Error {
+    match e {
+        CommentError::CantReply(_) => ErrorUnprocessableEntity(e),
+        CommentError::EditWindowClosed => ErrorForbidden(e),
+        CommentError::Database(_) => ErrorInternalServerError(e),
+    }
+}
+
+/// Responds with the saved article, or with the form's errors when it wasn't
+/// saved.
+async fn saved_article(
+    pool: &SqlitePool,
+    form: ArticleForm,
+    author: &UserRow,
+    created: bool,
+) -> Result<HttpResponse, Error> {
+    let slug = match &form.slug {
+        Some(slug) => slug,
+        None => {
+            return Ok(unprocessable([
+                ("title", form.title.errors.as_slice()),
+                ("description", form.description.errors.as_slice()),
+                ("body", form.body.errors.as_slice()),
+            ]))
+        }
+    };
+    let article = article::find_by_slug_as(pool, slug, Some(author.id))
+        .await
+        .map_err(ErrorInternalServerError)?
+        .ok_or_else(|| ErrorNotFound("article not found"))?;
+    let response = ArticleResponse {
+        article: article.into(),
+    };
+
+    if created {
+        Ok(HttpResponse::Created().json(response))
+    } else {
+        Ok(HttpResponse::Ok().json(response))
+    }
+}
+
+/// Responds with the article `slug` as `user` sees it after (un)favoriting it.
+async fn favorited_article(
+    pool: &SqlitePool,
+    slug: &str,
+    user: &UserRow,
+) -> Result<HttpResponse, Error> {
+    let article = article::find_by_slug_as(pool, slug, Some(user.id))
+        .await
+        .map_err(ErrorInternalServerError)?
+        .ok_or_else(|| ErrorNotFound("article not found"))?;
+
+    Ok(HttpResponse::Ok().json(ArticleResponse {
+        article: article.into(),
+    }))
+}
+
+#[post("/api/users/login")]
+async fn login(
+    pool: web::Data<SqlitePool>,
+    tokens: web::Data<Tokens>,
+    body: web::Json<UserBody<LoginUser>>,
+) -> Result<HttpResponse, Error> {
+    let LoginUser { email, password } = body.into_inner().user;
+    let (form, user) = LoginForm::submit(&pool, email, password)
+        .await
+        .map_err(ErrorInternalServerError)?;
+
+    match user {
+        Some(user) => Ok(HttpResponse::Ok().json(UserResponse::new(&tokens, user)?)),
+        None => Ok(unprocessable([
+            ("email", form.email.errors.as_slice()),
+            ("password", form.password.errors.as_slice()),
+            ("email or password", form.errors.as_slice()),
+        ])),
+    }
+}
+
+#[post("/api/users")]
+async fn register(
+    pool: web::Data<SqlitePool>,
+    tokens: web::Data<Tokens>,
+    body: web::Json<UserBody<NewUser>>,
+) -> Result<HttpResponse, Error> {
+    let NewUser {
+        username,
+        email,
+        password,
+    } = body.into_inner().user;
+    let (form, user) = RegisterForm::submit(&pool, username, email, password)
+        .await
+        .map_err(ErrorInternalServerError)?;
+
+    match user {
+        Some(user) => Ok(HttpResponse::Created().json(UserResponse::new(&tokens, user)?)),
+        None => Ok(unprocessable([
+            ("username", form.username.errors.as_slice()),
+            ("email", form.email.errors.as_slice()),
+            ("password", form.password.errors.as_slice()),
+        ])),
+    }
+}
+
+#[get("/api/user")]
+async fn current_user(
+    pool: web::Data<SqlitePool>,
+    tokens: web::Data<Tokens>,
+    current_user: CurrentUser,
+) -> Result<HttpResponse, Error> {
+    let user = user_row(&pool, &current_user).await?;
+
+    Ok(HttpResponse::Ok().json(UserResponse::new(&tokens, user)?))
+}
+
+#[put("/api/user")]
+async fn update_user(
+    pool: web::Data<SqlitePool>,
+    tokens: web::Data<Tokens>,
+    current_user: CurrentUser,
+    body: web::Json<UserBody<UserChanges>>,
+) -> Result<HttpResponse, Error> {
+    let user = user_row(&pool, &current_user).await?;
+    let UserChanges {
+        email,
+        username,
+        password,
+        image,
+        bio,
+        current_password,
+    } = body.into_inner().user;
+    let (form, saved) = SettingsForm::submit(
+        &pool,
+        &user,
+        image,
+        username,
+        bio,
+        email,
+        password,
+        current_password,
+    )
+    .await
+    .map_err(ErrorInternalServerError)?;
+
+    match saved {
+        Some(saved) => Ok(HttpResponse::Ok().json(UserResponse::new(&tokens, saved)?)),
+        None => Ok(unprocessable([
+            ("image", form.image.errors.as_slice()),
+            ("username", form.username.errors.as_slice()),
+            ("bio", form.bio.errors.as_slice()),
+            ("email", form.email.errors.as_slice()),
+            ("password", form.password.errors.as_slice()),
+            ("currentPassword", form.current_password.errors.as_slice()),
+        ])),
+    }
+}
+
+#[get("/api/profiles/{username}")]
+async fn get_profile(
+    pool: web::Data<SqlitePool>,
+    current_user: Option<CurrentUser>,
+    username: web::Path<String>,
+) -> Result<HttpResponse, Error> {
+    let user = find_user(&pool, &username).await?;
+    let viewer_id = viewer_id(&pool, &current_user).await?;
+    let profile = user::profile(&pool, user, viewer_id)
+        .await
+        .map_err(ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(ProfileResponse { profile }))
+}
+
+#[post("/api/profiles/{username}/follow")]
+async fn follow_user(
+    pool: web::Data<SqlitePool>,
+    current_user: CurrentUser,
+    username: web::Path<String>,
+) -> Result<HttpResponse, Error> {
+    let follower = user_row(&pool, &current_user).await?;
+    let user = find_user(&pool, &username).await?;
+    if follower.id == user.id {
+        return Err(ErrorForbidden("you can't follow yourself"));
+    }
+    user::follow(&pool, follower.id, user.id)
+        .await
+        .map_err(ErrorInternalServerError)?;
+    let profile = user::profile(&pool, user, Some(follower.id))
+        .await
+        .map_err(ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(ProfileResponse { profile }))
+}
+
+#[delete("/api/profiles/{username}/follow")]
+async fn unfollow_user(
+    pool: web::Data<SqlitePool>,
+    current_user: CurrentUser,
+    username: web::Path<String>,
+) -> Result<HttpResponse, Error> {
+    let follower = user_row(&pool, &current_user).await?;
+    let user = find_user(&pool, &username).await?;
+    user::unfollow(&pool, follower.id, user.id)
+        .await
+        .map_err(ErrorInternalServerError)?;
+    let profile = user::profile(&pool, user, Some(follower.id))
+        .await
+        .map_err(ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(ProfileResponse { profile }))
+}
+
+#[get("/api/articles")]
+async fn list_articles(
+    pool: web::Data<SqlitePool>,
+    current_user: Option<CurrentUser>,
+    query: web::Query<ArticlesQuery>,
+) -> Result<HttpResponse, Error> {
+    let viewer_id = viewer_id(&pool, &current_user).await?;
+    let ArticlesQuery {
+        tag,
+        author,
+        favorited,
+        limit,
+        offset,
+    } = query.into_inner();
+    let filter = ArticleFilter {
+        tag,
+        author,
+        favorited,
+        followed_by: None,
+    };
+
+    articles_page(&pool, &filter, viewer_id, limit, offset).await
+}
+
+#[get("/api/articles/feed")]
+async fn feed_articles(
+    pool: web::Data<SqlitePool>,
+    current_user: CurrentUser,
+    query: web::Query<PageQuery>,
+) -> Result<HttpResponse, Error> {
+    let user = user_row(&pool, &current_user).await?;
+    let filter = ArticleFilter {
+        followed_by: Some(user.id),
+        ..Default::default()
+    };
+    let PageQuery { limit, offset } = query.into_inner();
+
+    articles_page(&pool, &filter, Some(user.id), limit, offset).await
+}
+
+#[post("/api/articles")]
+async fn create_article(
+    pool: web::Data<SqlitePool>,
+    current_user: CurrentUser,
+    body: web::Json<ArticleBody<NewArticle>>,
+) -> Result<HttpResponse, Error> {
+    let author = user_row(&pool, &current_user).await?;
+    let NewArticle {
+        title,
+        description,
+        body,
+        tag_list,
+    } = body.into_inner().article;
+    let form = ArticleForm::new(title, description, body, tag_list);
+    let form = article::save(&pool, author.id, None, form)
+        .await
+        .map_err(ErrorInternalServerError)?;
+
+    saved_article(&pool, form, &author, true).await
+}
+
+#[get("/api/articles/{slug}")]
+async fn get_article(
+    pool: web::Data<SqlitePool>,
+    current_user: Option<CurrentUser>,
+    slug: web::Path<String>,
+) -> Result<HttpResponse, Error> {
+    let viewer_id = viewer_id(&pool, &current_user).await?;
+    let article = match article::find_by_slug_as(&pool, &slug, viewer_id)
+        .await
+        .map_err(ErrorInternalServerError)?
+    {
+        Some(article) => article,
+        None => {
+            let current = article::find_renamed(&pool, &slug)
+                .await
+                .map_err(ErrorInternalServerError)?
+                .ok_or_else(|| ErrorNotFound("article not found"))?;
+            return Ok(HttpResponse::MovedPermanently()
+                .insert_header((header::LOCATION, format!("/api/articles/{}", current)))
+                .finish());
+        }
+    };
+
+    Ok(HttpResponse::Ok().json(ArticleResponse {
+        article: article.into(),
+    }))
+}
+
+#[put("/api/articles/{slug}")]
+async fn update_article(
+    pool: web::Data<SqlitePool>,
+    current_user: CurrentUser,
+    slug: web::Path<String>,
+    body: web::Json<ArticleBody<ArticleChanges>>,
+) -> Result<HttpResponse, Error> {
+    let author = user_row(&pool, &current_user).await?;
+    let existing = own_article(&pool, &slug, &author).await?;
+    let changes = body.into_inner().article;
+    // Anything left out of the request keeps its current value.
+    let form = ArticleForm::new(
+        changes.title.unwrap_or(existing.title),
+        changes.description.unwrap_or(existing.description),
+        changes.body.unwrap_or(existing.body),
+        changes
+            .tag_list
+            .unwrap_or_else(|| serde_json::from_str(&existing.tag_list).unwrap_or_default()),
+    );
+    let form = article::save(&pool, author.id, Some(&existing.slug), form)
+        .await
+        .map_err(ErrorInternalServerError)?;
+
+    saved_article(&pool, form, &author, false).await
+}
+
+#[delete("/api/articles/{slug}")]
+async fn delete_article(
+    pool: web::Data<SqlitePool>,
+    current_user: CurrentUser,
+    slug: web::Path<String>,
+) -> Result<HttpResponse, Error> {
+    let author = user_row(&pool, &current_user).await?;
+    let existing = own_article(&pool, &slug, &author).await?;
+    article::delete(&pool, existing.id)
+        .await
+        .map_err(ErrorInternalServerError)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[post("/api/articles/{slug}/favorite")]
+async fn favorite_article(
+    pool: web::Data<SqlitePool>,
+    current_user: CurrentUser,
+    slug: web::Path<String>,
+) -> Result<HttpResponse, Error> {
+    let user = user_row(&pool, &current_user).await?;
+    let article = find_article(&pool, &slug).await?;
+    article::favorite(&pool, user.id, article.id)
+        .await
+        .map_err(ErrorInternalServerError)?;
+
+    favorited_article(&pool, &slug, &user).await
+}
+
+#[delete("/api/articles/{slug}/favorite")]
+async fn unfavorite_article(
+    pool: web::Data<SqlitePool>,
+    current_user: CurrentUser,
+    slug: web::Path<String>,
+) -> Result<HttpResponse, Error> {
+    let user = user_row(&pool, &current_user).await?;
+    let article = find_article(&pool, &slug).await?;
+    article::unfavorite(&pool, user.id, article.id)
+        .await
+        .map_err(ErrorInternalServerError)?;
+
+    favorited_article(&pool, &slug, &user).await
+}
+
+#[get("/api/articles/{slug}/comments")]
+async fn list_comments(
+    pool: web::Data<SqlitePool>,
+    current_user: Option<CurrentUser>,
+    slug: web::Path<String>,
+) -> Result<HttpResponse, Error> {
+    let viewer_id = viewer_id(&pool, &current_user).await?;
+    let article = find_article(&pool, &slug).await?;
+    let comments = comment::list(&pool, article.id, viewer_id)
+        .await
+        .map_err(ErrorInternalServerError)?
+        .into_iter()
+        .map(Comment::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(CommentsResponse { comments }))
+}
+
+#[post("/api/articles/{slug}/comments")]
+async fn add_comment(
+    pool: web::Data<SqlitePool>,
+    current_user: CurrentUser,
+    slug: web::Path<String>,
+    body: web::Json<CommentBody<NewComment>>,
+) -> Result<HttpResponse, Error> {
+    let author = user_row(&pool, &current_user).await?;
+    let article = find_article(&pool, &slug).await?;
+    let NewComment { body, parent_id } = body.into_inner().comment;
+    let form = comment::save(
+        &pool,
+        article.id,
+        author.id,
+        parent_id,
+        CommentForm::new(body),
+    )
+    .await
+    .map_err(comment_error)?;
+
+    match form.comment {
+        Some(comment) => Ok(HttpResponse::Ok().json(CommentResponse { comment })),
+        None => Ok(unprocessable([("body", form.body.errors.as_slice())])),
+    }
+}
+
+#[put("/api/articles/{slug}/comments/{id}")]
+async fn edit_comment(
+    pool: web::Data<SqlitePool>,
+    current_user: CurrentUser,
+    path: web::Path<(String, i64)>,
+    body: web::Json<CommentBody<CommentChanges>>,
+) -> Result<HttpResponse, Error> {
+    let (slug, id) = path.into_inner();
+    let author = user_row(&pool, &current_user).await?;
+    let article = find_article(&pool, &slug).await?;
+    let comment = own_comment(&pool, article.id, id, &author).await?;
+    let form = CommentForm::new(body.into_inner().comment.body);
+    let form = comment::save_edit(&pool, comment.id, form)
+        .await
+        .map_err(comment_error)?;
+
+    match form.comment {
+        Some(comment) => Ok(HttpResponse::Ok().json(CommentResponse { comment })),
+        None => Ok(unprocessable([("body", form.body.errors.as_slice())])),
+    }
+}
+
+#[delete("/api/articles/{slug}/comments/{id}")]
+async fn delete_comment(
+    pool: web::Data<SqlitePool>,
+    current_user: CurrentUser,
+    path: web::Path<(String, i64)>,
+) -> Result<HttpResponse, Error> {
+    let (slug, id) = path.into_inner();
+    let author = user_row(&pool, &current_user).await?;
+    let article = find_article(&pool, &slug).await?;
+    let comment = own_comment(&pool, article.id, id, &author).await?;
+    comment::delete(&pool, comment.id)
+        .await
+        .map_err(ErrorInternalServerError)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/api/tags")]
+async fn get_tags(pool: web::Data<SqlitePool>) -> Result<HttpResponse, Error> {
+    let tags = article::popular_tags(&pool, article::POPULAR_TAGS)
+        .await
+        .map_err(ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(TagsResponse { tags }))
+}
@@ -1,21 +1,53 @@
-use crate::validations::{Field, FieldError};
+use crate::article::{
+    get_tags, tag_href, use_page, ArticleList, ArticleListProps, ArticlePage, ArticlePageProps,
+    EditorPage, EditorPageProps,
+};
+use crate::profile::{ProfilePage, ProfilePageProps};
+use crate::user::User;
+use crate::validations::{Field, FieldError, Validate};
 use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
+use std::rc::Rc;
 
 #[cfg(feature = "ssr")]
 pub fn register_server_functions() {
     let _ = AttemptLogin::register();
+    let _ = RegisterUser::register();
     let _ = GetCurrentUser::register();
     let _ = Logout::register();
+    let _ = LogoutEverywhere::register();
+    let _ = GetActiveSessions::register();
+    let _ = GetSettings::register();
+    let _ = UpdateUser::register();
+    let _ = crate::article::CreateArticle::register();
+    let _ = crate::article::UpdateArticle::register();
+    let _ = crate::article::DeleteArticle::register();
+    let _ = crate::article::GetArticle::register();
+    let _ = crate::article::GetArticlePage::register();
+    let _ = crate::article::ListArticles::register();
+    let _ = crate::article::GetTags::register();
+    let _ = crate::article::FavoriteArticle::register();
+    let _ = crate::article::UnfavoriteArticle::register();
+    let _ = crate::profile::GetProfile::register();
+    let _ = crate::profile::FollowUser::register();
+    let _ = crate::profile::UnfollowUser::register();
+    let _ = crate::comment::AddComment::register();
+    let _ = crate::comment::EditComment::register();
+    let _ = crate::comment::ListComments::register();
+    let _ = crate::comment::DeleteComment::register();
 }
 
+#[cfg(feature = "ssr")]
+use crate::{db, jwt, password, session, user};
 #[cfg(feature = "ssr")]
 use actix_web::http::{
     header::HeaderMap, header::HeaderName, header::HeaderValue, header::SET_COOKIE, StatusCode,
 };
 #[cfg(feature = "ssr")]
 use actix_web::FromRequest;
+#[cfg(feature = "ssr")]
+use sqlx::SqlitePool;
 
 #[component]
 pub fn App(cx: Scope) -> impl IntoView {
@@ -51,10 +83,18 @@ pub fn App(cx: Scope) -> impl IntoView {
         <Router>
             <main>
                 <Routes>
-                    <Route path="" view=|cx| view! { cx, <HomePage/> }/>
+                    // Pages that can answer with a redirect render in async
+                    // mode: streaming would send the headers before it is set.
+                    <Route path="" view=|cx| view! { cx, <HomePage/> } ssr=SsrMode::Async/>
                     <Route path="login" view=|cx| view! { cx, <LoginPage/> }/>
+                    <Route path="register" view=|cx| view! { cx, <RegisterPage/> }/>
                     <Route path="logged-out" view=|cx| view! { cx, <LogoutPage/> }/>
-                    <Route path="settings" view=|cx| view! { cx, <SettingsPage/> }/>
+                    <Route path="settings" view=|cx| view! { cx, <SettingsPage/> } ssr=SsrMode::Async/>
+                    <Route path="editor" view=|cx| view! { cx, <EditorPage/> } ssr=SsrMode::Async/>
+                    <Route path="editor/:slug" view=|cx| view! { cx, <EditorPage/> } ssr=SsrMode::Async/>
+                    <Route path="article/:slug" view=|cx| view! { cx, <ArticlePage/> } ssr=SsrMode::Async/>
+                    <Route path="profile/:username" view=|cx| view! { cx, <ProfilePage favorites=false/> }/>
+                    <Route path="profile/:username/favorites" view=|cx| view! { cx, <ProfilePage favorites=true/> }/>
                 </Routes>
             </main>
         </Router>
@@ -75,6 +115,7 @@ fn get_errors<T>(
 
 #[component]
 fn LoginForm(cx: Scope) -> impl IntoView {
+    let query = use_query_map(cx);
     let attempt_login_form = create_server_multi_action::<AttemptLogin>(cx);
     let submissions = attempt_login_form.submissions();
     let pending_submissions = move || submissions.get().iter().find(|s| s.pending()()).is_some();
@@ -84,8 +125,9 @@ fn LoginForm(cx: Scope) -> impl IntoView {
 
         if let Some(Ok(res)) = &res {
             if res.is_valid() {
+                let next = after_login(query.with(|query| query.get("next").cloned()));
                 let nav = use_navigate(cx);
-                let _ = nav("/", Default::default());
+                let _ = nav(&next, Default::default());
             }
         }
 
@@ -94,6 +136,9 @@ fn LoginForm(cx: Scope) -> impl IntoView {
 
     view! {cx,
       <MultiActionForm action=attempt_login_form>
+        <FieldErrors errors=move || {
+            latest_result().and_then(|res| res.ok()).map(|res| res.errors).unwrap_or_default()
+        }/>
         <fieldset disabled=pending_submissions>
 
            // Email Address
@@ -116,7 +161,7 @@ fn LoginForm(cx: Scope) -> impl IntoView {
 }
 
 #[component]
-fn FieldErrors<E>(cx: Scope, errors: E) -> impl IntoView
+pub fn FieldErrors<E>(cx: Scope, errors: E) -> impl IntoView
 where
     E: Fn() -> Vec<FieldError> + 'static + Copy,
 {
@@ -131,6 +176,84 @@ where
     }
 }
 
+/// Percent-encodes `value` for use in a query string.
+pub fn encode_query_value(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// The login page, returning to `next` afterwards.
+pub fn login_href(next: &str) -> String {
+    format!("/login?next={}", encode_query_value(next))
+}
+
+/// Where to go after logging in: the `next` of the login page when it's a
+/// path on this site, so the parameter can't send anyone elsewhere.
+fn after_login(next: Option<String>) -> String {
+    next.filter(|next| next.starts_with('/') && !next.starts_with("//") && !next.contains('\\'))
+        .unwrap_or_else(|| "/".to_string())
+}
+
+/// Shows `children` to logged in users. Everyone else is sent to log in, and
+/// comes back here afterwards: with a `302` when the page is rendered on the
+/// server, and by navigating otherwise. The `302` needs the route to render
+/// with `SsrMode::Async`.
+#[component]
+pub fn RequireLogin(cx: Scope, children: Box<dyn Fn(Scope) -> Fragment>) -> impl IntoView {
+    let location = use_location(cx);
+    let here = move || {
+        let path = location.pathname.get();
+        match location.search.get().trim_start_matches('?') {
+            "" => path,
+            search => format!("{}?{}", path, search),
+        }
+    };
+    let logged_in = create_resource(
+        cx,
+        || (),
+        move |_| async move {
+            let logged_in = matches!(get_current_user(cx).await, Ok(Some(_)));
+            #[cfg(feature = "ssr")]
+            if !logged_in {
+                redirect(cx, StatusCode::FOUND, &login_href(&here()));
+            }
+            logged_in
+        },
+    );
+    create_effect(cx, move |_| {
+        if logged_in.read() == Some(false) {
+            let nav = use_navigate(cx);
+            let _ = nav(
+                &login_href(&here()),
+                NavigateOptions {
+                    replace: true,
+                    ..Default::default()
+                },
+            );
+        }
+    });
+    let children = Rc::new(children);
+
+    view! {cx,
+      <Suspense fallback=move || view! {cx, <p>"Loading..."</p>}>
+        {let children = children.clone();
+         move || logged_in.read().map(|logged_in| if logged_in {
+            children(cx).into_view(cx)
+        } else {
+            view! {cx, <p>"Redirecting to log in..."</p>}.into_view(cx)
+        })}
+      </Suspense>
+    }
+}
+
 #[component]
 fn LoginPage(cx: Scope) -> impl IntoView {
     view! {cx,
@@ -141,8 +264,7 @@ fn LoginPage(cx: Scope) -> impl IntoView {
               <div class="col-md-6 offset-md-3 col-xs-12">
                 <h1 class="text-xs-center">"Log In"</h1>
                 <p class="text-xs-center">
-                  // TODO: Hook up this link
-                  <a href="">"Don't Have an account?"</a>
+                  <A href="/register">"Don't Have an account?"</A>
                 </p>
                 <LoginForm/>
               </div>
@@ -171,10 +293,13 @@ fn LogoutPage(cx: Scope) -> impl IntoView {
 #[component]
 fn SettingsPage(cx: Scope) -> impl IntoView {
     let logout_action = create_server_action::<Logout>(cx);
+    let logout_everywhere_action = create_server_action::<LogoutEverywhere>(cx);
     // TODO: how do we make this just a POST and not a server action?
     create_effect(cx, move |_| {
         console_log("logout action run");
-        if let Some(Ok(_)) = &logout_action.value().get() {
+        let logged_out = matches!(&logout_action.value().get(), Some(Ok(_)))
+            || matches!(&logout_everywhere_action.value().get(), Some(Ok(_)));
+        if logged_out {
             let nav = use_navigate(cx);
             let _ = nav("/logged-out", Default::default());
         }
@@ -188,34 +313,18 @@ fn SettingsPage(cx: Scope) -> impl IntoView {
           <div class="col-md-6 offset-md-3 col-xs-12">
             <h1 class="text-xs-center">"Your Settings"</h1>
 
-            <form>
-              <fieldset>
-                <fieldset class="form-group">
-                  <input class="form-control" type="text" placeholder="URL of profile picture" />
-                </fieldset>
-                <fieldset class="form-group">
-                  <input class="form-control form-control-lg" type="text" placeholder="Your Name" />
-                </fieldset>
-                <fieldset class="form-group">
-                  <textarea
-                    class="form-control form-control-lg"
-                    rows="8"
-                    placeholder="Short bio about you"
-                  ></textarea>
-                </fieldset>
-                <fieldset class="form-group">
-                  <input class="form-control form-control-lg" type="text" placeholder="Email" />
-                </fieldset>
-                <fieldset class="form-group">
-                  <input class="form-control form-control-lg" type="password" placeholder="Password" />
-                </fieldset>
-                <button class="btn btn-lg btn-primary pull-xs-right">"Update Settings"</button>
-              </fieldset>
-            </form>
-            <hr />
-            <ActionForm action=logout_action>
-              <button class="btn btn-outline-danger">"Or click here to logout."</button>
-            </ActionForm>
+            <RequireLogin>
+              <CurrentSettings/>
+              <hr />
+              <ActionForm action=logout_action>
+                <button class="btn btn-outline-danger">"Or click here to logout."</button>
+              </ActionForm>
+              <hr />
+              <ActiveSessions/>
+              <ActionForm action=logout_everywhere_action>
+                <button class="btn btn-outline-danger">"Log out all my devices"</button>
+              </ActionForm>
+            </RequireLogin>
           </div>
         </div>
       </div>
@@ -223,6 +332,185 @@ fn SettingsPage(cx: Scope) -> impl IntoView {
     }
 }
 
+/// Loads the current user's details into the settings form.
+#[component]
+fn CurrentSettings(cx: Scope) -> impl IntoView {
+    let settings = create_resource(cx, || (), move |_| get_settings(cx));
+
+    view! {cx,
+      <Suspense fallback=move || view! {cx, <p>"Loading..."</p>}>
+        {move || settings.read().map(|user| match user {
+            Ok(Some(user)) => view! {cx, <SettingsEditor user=user/>}.into_view(cx),
+            _ => view! {cx, <p>"Log in to change your settings."</p>}.into_view(cx),
+        })}
+      </Suspense>
+    }
+}
+
+/// The settings form, filled in with `user`'s current details.
+#[component]
+fn SettingsEditor(cx: Scope, user: User) -> impl IntoView {
+    let update_user = create_server_action::<UpdateUser>(cx);
+    let result = update_user.value();
+    let errors = move |field: fn(SettingsForm) -> Field<String>| {
+        move || match result.get() {
+            Some(Ok(form)) => field(form).errors,
+            _ => vec![],
+        }
+    };
+    let notice = move || match result.get() {
+        Some(Ok(form)) if form.user.is_some() => {
+            Some(view! {cx, <p class="text-success">"Your settings have been saved."</p>})
+        }
+        Some(Err(_)) => {
+            Some(view! {cx, <p class="text-danger">"Your settings couldn't be saved."</p>})
+        }
+        _ => None,
+    };
+
+    view! {cx,
+      {notice}
+      <ActionForm action=update_user>
+        <fieldset disabled=move || update_user.pending().get()>
+          <fieldset class="form-group">
+            <FieldErrors errors=errors(|form| form.image)/>
+            <input
+              class="form-control"
+              type="text"
+              placeholder="URL of profile picture"
+              name="image"
+              value=user.image.unwrap_or_default()
+            />
+          </fieldset>
+          <fieldset class="form-group">
+            <FieldErrors errors=errors(|form| form.username)/>
+            <input
+              class="form-control form-control-lg"
+              type="text"
+              placeholder="Your Name"
+              name="username"
+              value=user.username
+            />
+          </fieldset>
+          <fieldset class="form-group">
+            <FieldErrors errors=errors(|form| form.bio)/>
+            <textarea
+              class="form-control form-control-lg"
+              rows="8"
+              placeholder="Short bio about you"
+              name="bio"
+            >
+              {user.bio.unwrap_or_default()}
+            </textarea>
+          </fieldset>
+          <fieldset class="form-group">
+            <FieldErrors errors=errors(|form| form.email)/>
+            <input
+              class="form-control form-control-lg"
+              type="text"
+              placeholder="Email"
+              name="email"
+              value=user.email
+            />
+          </fieldset>
+          <fieldset class="form-group">
+            <FieldErrors errors=errors(|form| form.password)/>
+            <input
+              class="form-control form-control-lg"
+              type="password"
+              placeholder="New Password"
+              name="password"
+            />
+          </fieldset>
+          <fieldset class="form-group">
+            <FieldErrors errors=errors(|form| form.current_password)/>
+            <input
+              class="form-control form-control-lg"
+              type="password"
+              placeholder="Current Password, to change your password or email"
+              name="current_password"
+            />
+          </fieldset>
+          <button class="btn btn-lg btn-primary pull-xs-right">"Update Settings"</button>
+        </fieldset>
+      </ActionForm>
+    }
+}
+
+#[component]
+fn ActiveSessions(cx: Scope) -> impl IntoView {
+    let sessions = create_resource(cx, || (), move |_| get_active_sessions(cx));
+
+    view! {cx,
+      <h4>"Active sessions"</h4>
+      <Suspense fallback=move || view! {cx, <p>"Loading..."</p>}>
+        <ul class="list-unstyled">
+          {move || sessions.read().map(|sessions| {
+              sessions
+                  .unwrap_or_default()
+                  .into_iter()
+                  .map(|session| view! {cx,
+                    <li>
+                      <strong>{session.user_agent.unwrap_or_else(|| "Unknown device".to_string())}</strong>
+                      <br/>
+                      <small>"Signed in " {session.created_at} ", last seen " {session.last_seen_at}</small>
+                    </li>
+                  })
+                  .collect::<Vec<_>>()
+          })}
+        </ul>
+      </Suspense>
+    }
+}
+
+#[component]
+fn RegisterForm(cx: Scope) -> impl IntoView {
+    let register_form = create_server_multi_action::<RegisterUser>(cx);
+    let submissions = register_form.submissions();
+    let pending_submissions = move || submissions.get().iter().find(|s| s.pending()()).is_some();
+    let latest_result = move || {
+        let subs = submissions.get();
+        let res = subs.iter().last().map(|s| s.value.get()).unwrap_or(None);
+
+        if let Some(Ok(res)) = &res {
+            if res.is_valid() {
+                let nav = use_navigate(cx);
+                let _ = nav("/", Default::default());
+            }
+        }
+
+        res
+    };
+
+    view! {cx,
+      <MultiActionForm action=register_form>
+        <fieldset disabled=pending_submissions>
+
+          // Username
+          <fieldset class="form-group">
+            <FieldErrors errors=move || get_errors(&latest_result, &|res| res.username)/>
+            <input class="form-control form-control-lg" type="text" placeholder="Your Name" name="username"/>
+          </fieldset>
+
+          // Email Address
+          <fieldset class="form-group">
+            <FieldErrors errors=move || get_errors(&latest_result, &|res| res.email)/>
+            <input class="form-control form-control-lg" type="text" placeholder="Email" name="email"/>
+          </fieldset>
+
+          // Password
+          <fieldset class="form-group">
+            <FieldErrors errors=move || get_errors(&latest_result, &|res| res.password)/>
+            <input class="form-control form-control-lg" type="password" placeholder="Password" name="password"/>
+          </fieldset>
+
+          // Submit
+          <button class="btn btn-lg btn-primary pull-xs-right">"Sign up"</button>
+        </fieldset>
+      </MultiActionForm>
+    }
+}
+
 #[component]
 fn RegisterPage(cx: Scope) -> impl IntoView {
     view! {cx,
@@ -233,27 +521,9 @@ fn RegisterPage(cx: Scope) -> impl IntoView {
               <div class="col-md-6 offset-md-3 col-xs-12">
                 <h1 class="text-xs-center">"Sign up"</h1>
                 <p class="text-xs-center">
-                  <a href="">"Have an account?"</a>
+                  <A href="/login">"Have an account?"</A>
                 </p>
-
-                <ul class="error-messages">
-                  <li>"That email is already taken"</li>
-                </ul>
-
-                <form>
-                  <fieldset class="form-group">
-                    <input class="form-control form-control-lg" type="text" placeholder="Your Name" />
-                  </fieldset>
-                  <fieldset class="form-group">
-                    <input class="form-control form-control-lg" type="text" placeholder="Email" />
-                  </fieldset>
-                  <fieldset class="form-group">
-                    <input class="form-control form-control-lg" type="password" placeholder="Password" />
-                  </fieldset>
-                  <button class="btn btn-lg btn-primary pull-xs-right">"Sign up"</button>
-                </form>
-
-
+                <RegisterForm/>
               </div>
             </div>
           </div>
@@ -261,9 +531,22 @@ fn RegisterPage(cx: Scope) -> impl IntoView {
     }
 }
 
-/// Renders the home page of your application.
+/// Renders the home page of your application. `?tag=` shows articles with that
+/// tag, `?feed=your` articles by followed authors, anything else the global
+/// feed.
 #[component]
 fn HomePage(cx: Scope) -> impl IntoView {
+    let query = use_query_map(cx);
+    let tag = move || query.with(|query| query.get("tag").filter(|tag| !tag.is_empty()).cloned());
+    let your_feed = move || {
+        tag().is_none() && query.with(|query| query.get("feed").map(String::as_str) == Some("your"))
+    };
+    let global_feed = move || tag().is_none() && !your_feed();
+    let tags = create_resource(cx, || (), move |_| get_tags(cx));
+    let page = use_page(cx);
+    let current_user = create_resource(cx, || (), move |_| get_current_user(cx));
+    let logged_in = move || matches!(current_user.read(), Some(Ok(Some(_))));
+
     view! { cx,
       <div class="home-page">
         // Header
@@ -279,23 +562,93 @@ fn HomePage(cx: Scope) -> impl IntoView {
               <div class="feed-toggle">
                 <ul class="nav nav-pills outline-active">
                   <li class="nav-item">
-                    <a class="nav-link disabled" href="">"Your Feed"</a>
+                    <Suspense fallback=move || view! {cx, <a class="nav-link disabled">"Your Feed"</a>}>
+                      {move || if logged_in() {
+                          view! {cx,
+                            <A
+                              class=if your_feed() { "nav-link active" } else { "nav-link" }
+                              href="/?feed=your"
+                            >
+                              "Your Feed"
+                            </A>
+                          }
+                          .into_view(cx)
+                      } else {
+                          view! {cx, <a class="nav-link disabled">"Your Feed"</a>}.into_view(cx)
+                      }}
+                    </Suspense>
                   </li>
                   <li class="nav-item">
-                    <a class="nav-link active" href="">"Global Feed"</a>
+                    {move || view! {cx,
+                      <A
+                        class=if global_feed() { "nav-link active" } else { "nav-link" }
+                        href="/?feed=global"
+                      >
+                        "Global Feed"
+                      </A>
+                    }}
                   </li>
+                  {move || tag().map(|tag| view! {cx,
+                    <li class="nav-item">
+                      <A class="nav-link active" href=tag_href(&tag)>
+                        <i class="ion-pound"></i>" " {tag}
+                      </A>
+                    </li>
+                  })}
                 </ul>
               </div>
 
               // Article Previews
-              <ArticlePreview/>
+              {move || {
+                  let tag = tag();
+                  let feed = your_feed();
+                  let href = match &tag {
+                      Some(tag) => tag_href(tag),
+                      None if feed => "/?feed=your".to_string(),
+                      None => "/?feed=global".to_string(),
+                  };
+                  let page_href = move |page| format!("{}&page={}", href, page);
+                  if feed {
+                      view! {cx,
+                        <RequireLogin>
+                          <ArticleList
+                            tag=None
+                            author=None
+                            favorited=None
+                            feed=true
+                            page=page()
+                            page_href=page_href.clone()
+                          />
+                        </RequireLogin>
+                      }
+                      .into_view(cx)
+                  } else {
+                      view! {cx,
+                        <ArticleList
+                          tag=tag
+                          author=None
+                          favorited=None
+                          feed=false
+                          page=page()
+                          page_href=page_href
+                        />
+                      }
+                      .into_view(cx)
+                  }
+              }}
             </div>
 
             // Sidebar
             <div class="col-md-3">
               <div class="sidebar">
                 <p>"Popular Tags"</p>
-                  <TagList tags=vec!["Greg", "Leptos"]/>
+                <Suspense fallback=move || view! {cx, <div>"Loading tags..."</div>}>
+                  {move || tags.read().map(|tags| match tags {
+                      Ok(tags) if !tags.is_empty() => view! {cx, <TagList tags=tags/>}.into_view(cx),
+                      Ok(_) => view! {cx, <div>"No tags are here... yet."</div>}.into_view(cx),
+                      Err(_) => view! {cx, <div>"Tags couldn't be loaded."</div>}.into_view(cx),
+                  })}
+                </Suspense>
               </div>
             </div>
 
@@ -309,44 +662,8 @@ fn HomePage(cx: Scope) -> impl IntoView {
     }
 }
 
-struct ArticlePreview {
-    author_name: String,
-    date: String,
-    title: String,
-    blurb: String,
-}
-
-#[component]
-fn ArticlePreview(cx: Scope) -> impl IntoView {
-    let preview = ArticlePreview {
-        author_name: "Eric Simons".to_string(),
-        date: "January 20th".to_string(),
-        title: "How to build webapps that scale".to_string(),
-        blurb: "This is the description for the post.".to_string(),
-    };
-    view! {cx,
-      <div class="article-preview">
-        <div class="article-meta">
-          <a href="profile.html"><img src="http://i.imgur.com/Qr71crq.jpg" /></a>
-          <div class="info">
-            <a href="" class="author">{preview.author_name}</a>
-            <span class="date">{preview.date}</span>
-          </div>
-          <button class="btn btn-outline-primary btn-sm pull-xs-right">
-            <i class="ion-heart"></i> 29
-          </button>
-        </div>
-        <a href="" class="preview-link">
-          <h1>{preview.title}</h1>
-          <p>{preview.blurb}</p>
-          <span>"Read more..."</span>
-        </a>
-      </div>
-    }
-}
-
 #[component]
-fn Header(cx: Scope) -> impl IntoView {
+pub fn Header(cx: Scope) -> impl IntoView {
     let current_user = create_server_action::<GetCurrentUser>(cx);
     current_user.dispatch(GetCurrentUser {});
 
@@ -363,7 +680,7 @@ fn Header(cx: Scope) -> impl IntoView {
               <A class="nav-link" href="/login">"Sign in"</A>
             </li>
             <li class="nav-item">
-              <a class="nav-link" href="">"Sign up"</a>
+              <A class="nav-link" href="/register">"Sign up"</A>
             </li>
         }}>
 
@@ -378,7 +695,7 @@ fn Header(cx: Scope) -> impl IntoView {
               <span>"logged in as:" {current_user.value().get().unwrap().unwrap().unwrap().email}</span>
             </li>
             <li class="nav-item">
-              <a class="nav-link" href=""> <i class="ion-compose"></i>" New Article "</a>
+              <A class="nav-link" href="/editor"> <i class="ion-compose"></i>" New Article "</A>
             </li>
        </Show>
           </ul>
@@ -410,10 +727,9 @@ fn Footer(cx: Scope) -> impl IntoView {
     }
 }
 
+/// Tags linking to the home page feed of their articles.
 #[component]
-fn TagList(cx: Scope, tags: Vec<&'static str>) -> impl IntoView {
-    // TODO: make this nicer:
-    let tags: Vec<String> = tags.iter().map(|s| s.to_string()).collect();
+fn TagList(cx: Scope, tags: Vec<String>) -> impl IntoView {
     view! { cx,
       <div class="tag-list">
         <For
@@ -421,7 +737,7 @@ fn TagList(cx: Scope, tags: Vec<&'static str>) -> impl IntoView {
           key=|tag| tag.clone()
           view=move |tag: String| {
               view! { cx,
-                  <a href="" class="tag-pill tag-default">{tag}</a>
+                  <A href=tag_href(&tag) class="tag-pill tag-default">{tag}</A>
               }
           }
         />
@@ -429,15 +745,42 @@ fn TagList(cx: Scope, tags: Vec<&'static str>) -> impl IntoView {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, Validate)]
 pub struct LoginForm {
+    #[validate(required, trim, email, normalize_email)]
     pub email: Field<String>,
+    #[validate(required, trim, min_length = 10)]
     pub password: Field<String>,
+    /// Errors that belong to the form as a whole rather than a single field.
+    #[validate(form)]
+    pub errors: Vec<FieldError>,
 }
 
+#[cfg(feature = "ssr")]
 impl LoginForm {
-    pub fn is_valid(&self) -> bool {
-        self.email.errors.is_empty() && self.password.errors.is_empty()
+    /// Validates a login attempt and checks the credentials against the user
+    /// store. Shared by `AttemptLogin` and the JSON API.
+    pub async fn submit(
+        pool: &SqlitePool,
+        email: String,
+        password: String,
+    ) -> Result<(LoginForm, Option<user::UserRow>), ServerFnError> {
+        let mut form = LoginForm::from_inputs(Some(email), Some(password));
+
+        if !form.is_valid() {
+            return Ok((form, None));
+        }
+
+        let email = form.email.input.clone().unwrap_or_default();
+        let password = form.password.input.clone().unwrap_or_default();
+        let user = user::authenticate(pool, &email, &password)
+            .await
+            .map_err(db::server_error)?;
+        if user.is_none() {
+            form.errors.push(FieldError::InvalidCredentials);
+        }
+
+        Ok((form, user))
     }
 }
 
@@ -449,27 +792,337 @@ pub async fn attempt_login(
 ) -> Result<LoginForm, ServerFnError> {
     let req = use_context::<actix_web::HttpRequest>(cx).unwrap();
     let sess = actix_session::Session::extract(&req).await.unwrap();
-    let form = LoginForm {
-        email: Field::required(Some(email)).trim().min_length(10).email(),
-        password: Field::required(Some(password)).trim().min_length(10),
-    };
+    let pool = db::pool(cx)?;
+    let (form, user) = LoginForm::submit(&pool, email, password).await?;
+
+    if let Some(user) = user {
+        session::start(&req, &sess, user.id).map_err(db::server_error)?;
+    }
+
+    Ok(form)
+}
 
-    if form.is_valid() {
-        sess.insert("user_email", form.email.clone().input.unwrap());
-        Ok(form)
+/// Keeps usernames to characters that need no escaping in the `/profile/`
+/// links and API paths they're put in.
+fn url_safe(username: &str) -> Result<(), String> {
+    let safe = username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if safe {
+        Ok(())
     } else {
-        Ok(form)
+        Err("Usernames can only use letters, digits, '-', '_' and '.'.".to_string())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, Validate)]
+pub struct RegisterForm {
+    #[validate(required, trim, min_length = 3, custom = |name: &String| url_safe(name))]
+    pub username: Field<String>,
+    #[validate(required, trim, email, normalize_email)]
+    pub email: Field<String>,
+    #[validate(required, trim, min_length = 10)]
+    pub password: Field<String>,
+}
+
+#[cfg(feature = "ssr")]
+impl RegisterForm {
+    /// Validates a registration and creates the account when everything
+    /// checks out. Shared by `RegisterUser` and the JSON API.
+    pub async fn submit(
+        pool: &SqlitePool,
+        username: String,
+        email: String,
+        password: String,
+    ) -> Result<(RegisterForm, Option<user::UserRow>), ServerFnError> {
+        let mut form = RegisterForm::from_inputs(Some(username), Some(email), Some(password));
+
+        check_available(pool, &mut form.username, &mut form.email, None).await?;
+
+        if !form.is_valid() {
+            return Ok((form, None));
+        }
+
+        let username = form.username.input.clone().unwrap_or_default();
+        let email = form.email.input.clone().unwrap_or_default();
+        let password = form.password.input.clone().unwrap_or_default();
+        let password_hash = password::hash_off_worker(password)
+            .await
+            .map_err(db::server_error)?;
+        let user = match user::create(pool, &username, &email, &password_hash).await {
+            Ok(user) => user,
+            Err(e) => {
+                taken_meanwhile(e, &mut form.username, &mut form.email)?;
+                return Ok((form, None));
+            }
+        };
+
+        Ok((form, Some(user)))
+    }
+}
+
+/// Marks `username` and `email` as taken when someone other than the user
+/// `except` already has them.
+#[cfg(feature = "ssr")]
+async fn check_available(
+    pool: &SqlitePool,
+    username: &mut Field<String>,
+    email: &mut Field<String>,
+    except: Option<i64>,
+) -> Result<(), ServerFnError> {
+    username
+        .check_async(|username| async move {
+            let taken = user::username_taken(pool, &username, except).await?;
+            Ok::<_, sqlx::Error>(taken.then_some(FieldError::Taken))
+        })
+        .await
+        .map_err(db::server_error)?;
+    email
+        .check_async(|email| async move {
+            let taken = user::email_taken(pool, &email, except).await?;
+            Ok::<_, sqlx::Error>(taken.then_some(FieldError::Taken))
+        })
+        .await
+        .map_err(db::server_error)
+}
+
+/// Marks `username` or `email` as taken when `e` says someone else got it
+/// after `check_available` looked. Passes any other error on.
+#[cfg(feature = "ssr")]
+fn taken_meanwhile(
+    e: sqlx::Error,
+    username: &mut Field<String>,
+    email: &mut Field<String>,
+) -> Result<(), ServerFnError> {
+    match user::duplicate_column(&e) {
+        Some("username") => username.errors.push(FieldError::Taken),
+        Some(_) => email.errors.push(FieldError::Taken),
+        None => return Err(db::server_error(e)),
+    }
+    Ok(())
+}
+
+#[server(RegisterUser, "/api")]
+pub async fn register_user(
+    cx: Scope,
+    username: String,
+    email: String,
+    password: String,
+) -> Result<RegisterForm, ServerFnError> {
+    let req = use_context::<actix_web::HttpRequest>(cx).unwrap();
+    let sess = actix_session::Session::extract(&req).await.unwrap();
+    let pool = db::pool(cx)?;
+    let (form, user) = RegisterForm::submit(&pool, username, email, password).await?;
+
+    if let Some(user) = user {
+        session::start(&req, &sess, user.id).map_err(db::server_error)?;
+    }
+
+    Ok(form)
+}
+
+/// The settings page's changes to the current user.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, Validate)]
+pub struct SettingsForm {
+    #[validate(url)]
+    pub image: Field<String>,
+    #[validate(required, trim, min_length = 3, custom = |name: &String| url_safe(name))]
+    pub username: Field<String>,
+    #[validate]
+    pub bio: Field<String>,
+    #[validate(required, trim, email, normalize_email)]
+    pub email: Field<String>,
+    /// Left out to keep the current password.
+    #[validate(min_length = 10)]
+    pub password: Field<String>,
+    /// Needed to change the password or email.
+    #[validate]
+    pub current_password: Field<String>,
+    /// The user as saved, once every field checks out.
+    pub user: Option<User>,
+}
+
+#[cfg(feature = "ssr")]
+impl SettingsForm {
+    /// Validates changes to `user` and saves them when everything checks out.
+    /// Whatever is `None` keeps its current value, and a blank image or bio
+    /// clears it. A new password or email takes the current password, and
+    /// signs the user out everywhere. Shared by `UpdateUser` and the JSON API.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn submit(
+        pool: &SqlitePool,
+        user: &user::UserRow,
+        image: Option<String>,
+        username: Option<String>,
+        bio: Option<String>,
+        email: Option<String>,
+        password: Option<String>,
+        current_password: Option<String>,
+    ) -> Result<(SettingsForm, Option<user::UserRow>), ServerFnError> {
+        let blank_to_none = |input: String| {
+            let input = input.trim().to_string();
+            (!input.is_empty()).then_some(input)
+        };
+        let mut form = SettingsForm::from_inputs(
+            image.map_or_else(|| user.image.clone(), blank_to_none),
+            Some(username.unwrap_or_else(|| user.username.clone())),
+            bio.map_or_else(|| user.bio.clone(), blank_to_none),
+            Some(email.unwrap_or_else(|| user.email.clone())),
+            password.and_then(blank_to_none),
+            current_password.and_then(blank_to_none),
+        );
+
+        check_available(pool, &mut form.username, &mut form.email, Some(user.id)).await?;
+
+        let email_changed = form
+            .email
+            .input
+            .as_ref()
+            .is_some_and(|email| !email.eq_ignore_ascii_case(&user.email));
+        let sign_out = form.password.input.is_some() || email_changed;
+        if sign_out {
+            match form.current_password.input.clone() {
+                None => form.current_password.errors.push(FieldError::Required),
+                Some(current) => {
+                    let verification =
+                        password::verify_off_worker(current, user.password_hash.clone()).await;
+                    if verification == password::Verification::Invalid {
+                        form.current_password.errors.push(FieldError::WrongPassword);
+                    }
+                }
+            }
+        }
+
+        if !form.is_valid() {
+            return Ok((form, None));
+        }
+
+        let password_hash = match form.password.input.clone() {
+            Some(password) => password::hash_off_worker(password)
+                .await
+                .map_err(db::server_error)?,
+            None => user.password_hash.clone(),
+        };
+        let details = User {
+            email: form.email.input.clone().unwrap_or_default(),
+            username: form.username.input.clone().unwrap_or_default(),
+            bio: form.bio.input.clone(),
+            image: form.image.input.clone(),
+        };
+        let saved = match user::update(pool, user.id, &details, &password_hash, sign_out).await {
+            Ok(saved) => saved,
+            Err(e) => {
+                taken_meanwhile(e, &mut form.username, &mut form.email)?;
+                return Ok((form, None));
+            }
+        };
+        form.user = Some(saved.clone().into());
+
+        Ok((form, Some(saved)))
+    }
+}
+
+/// The current user, to fill in the settings page.
+#[server(GetSettings, "/api")]
+pub async fn get_settings(cx: Scope) -> Result<Option<User>, ServerFnError> {
+    Ok(logged_in_user(cx).await?.map(User::from))
+}
+
+#[server(UpdateUser, "/api")]
+pub async fn update_user(
+    cx: Scope,
+    image: String,
+    username: String,
+    bio: String,
+    email: String,
+    password: String,
+    current_password: String,
+) -> Result<SettingsForm, ServerFnError> {
+    let req = use_context::<actix_web::HttpRequest>(cx).unwrap();
+    let sess = actix_session::Session::extract(&req).await.unwrap();
+    let pool = db::pool(cx)?;
+    let current = require_user(cx).await?;
+    let (form, saved) = SettingsForm::submit(
+        &pool,
+        &current,
+        Some(image),
+        Some(username),
+        Some(bio),
+        Some(email),
+        Some(password),
+        Some(current_password),
+    )
+    .await?;
+
+    // Saving a new password or email ended every session, this one included.
+    // Renewing stores it again under a new key, so only this device stays
+    // logged in.
+    if let Some(saved) = saved {
+        if saved.token_version != current.token_version {
+            sess.renew();
+        }
     }
+
+    Ok(form)
 }
 
 #[server(Logout, "/api")]
 pub async fn logout(cx: Scope) -> Result<(), ServerFnError> {
     let req = use_context::<actix_web::HttpRequest>(cx).unwrap();
     let sess = actix_session::Session::extract(&req).await.unwrap();
-    sess.clear();
+    sess.purge();
     Ok(())
 }
 
+/// Ends every session of the current user and revokes their API tokens.
+#[server(LogoutEverywhere, "/api")]
+pub async fn logout_everywhere(cx: Scope) -> Result<(), ServerFnError> {
+    let req = use_context::<actix_web::HttpRequest>(cx).unwrap();
+    let sess = actix_session::Session::extract(&req).await.unwrap();
+    let pool = db::pool(cx)?;
+    let current_user = require_user(cx).await?;
+
+    session::delete_for_user(&pool, current_user.id)
+        .await
+        .map_err(db::server_error)?;
+    user::revoke_tokens(&pool, current_user.id)
+        .await
+        .map_err(db::server_error)?;
+    sess.purge();
+    Ok(())
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ActiveSession {
+    pub id: i64,
+    pub user_agent: Option<String>,
+    pub created_at: String,
+    pub last_seen_at: String,
+}
+
+#[server(GetActiveSessions, "/api")]
+pub async fn get_active_sessions(cx: Scope) -> Result<Vec<ActiveSession>, ServerFnError> {
+    let req = use_context::<actix_web::HttpRequest>(cx).unwrap();
+    let pool = db::pool(cx)?;
+    let current_user = match CurrentUser::extract(&req).await {
+        Ok(current_user) => current_user,
+        Err(_) => return Ok(vec![]),
+    };
+
+    let sessions = session::list_for_user(&pool, current_user.id)
+        .await
+        .map_err(db::server_error)?;
+    Ok(sessions
+        .into_iter()
+        .map(|row| ActiveSession {
+            id: row.id,
+            user_agent: row.user_agent,
+            created_at: row.created_at,
+            last_seen_at: row.last_seen_at,
+        })
+        .collect())
+}
+
 #[cfg(feature = "ssr")]
 fn set_header(cx: &Scope, key: HeaderName, val: HeaderValue) {
     let res_options_outer = use_context::<leptos_actix::ResponseOptions>(*cx);
@@ -478,17 +1131,66 @@ fn set_header(cx: &Scope, key: HeaderName, val: HeaderValue) {
     }
 }
 
-#[server(GetCurrentUser, "/api")]
-pub async fn get_current_user(cx: Scope) -> Result<Option<CurrentUser>, ServerFnError> {
-    match use_context::<actix_web::HttpRequest>(cx) {
-        Some(req) => Ok(CurrentUser::extract(&req).await.ok()),
-        None => Ok(None),
+/// Answers a page request with a `301` to `location`. Like any redirect,
+/// only takes effect on routes rendered with `SsrMode::Async`.
+#[cfg(feature = "ssr")]
+pub(crate) fn redirect_permanently(cx: Scope, location: &str) {
+    redirect(cx, StatusCode::MOVED_PERMANENTLY, location)
+}
+
+/// Answers a page request with a `status` redirect to `location`. Server
+/// function calls are left alone, since their callers expect the function's
+/// own response.
+#[cfg(feature = "ssr")]
+fn redirect(cx: Scope, status: StatusCode, location: &str) {
+    let is_page = use_context::<actix_web::HttpRequest>(cx)
+        .map(|req| !req.path().starts_with("/api/"))
+        .unwrap_or(false);
+    if !is_page {
+        return;
+    }
+    if let Some(res_options) = use_context::<leptos_actix::ResponseOptions>(cx) {
+        res_options.set_status(status);
     }
+    if let Ok(location) = HeaderValue::from_str(location) {
+        set_header(&cx, actix_web::http::header::LOCATION, location);
+    }
+}
+
+#[server(GetCurrentUser, "/api")]
+pub async fn get_current_user(cx: Scope) -> Result<Option<User>, ServerFnError> {
+    Ok(logged_in_user(cx).await?.map(User::from))
+}
+
+/// Loads the full record of whoever is making the current request.
+#[cfg(feature = "ssr")]
+pub async fn logged_in_user(cx: Scope) -> Result<Option<user::UserRow>, ServerFnError> {
+    let req = match use_context::<actix_web::HttpRequest>(cx) {
+        Some(req) => req,
+        None => return Ok(None),
+    };
+    let current_user = match CurrentUser::extract(&req).await {
+        Ok(current_user) => current_user,
+        Err(_) => return Ok(None),
+    };
+
+    user::find_by_id(&db::pool(cx)?, current_user.id)
+        .await
+        .map_err(db::server_error)
 }
 
+/// Like `logged_in_user`, for server functions that need someone logged in.
+#[cfg(feature = "ssr")]
+pub async fn require_user(cx: Scope) -> Result<user::UserRow, ServerFnError> {
+    logged_in_user(cx)
+        .await?
+        .ok_or_else(|| ServerFnError::ServerError("not logged in".to_string()))
+}
+
+/// The id of whoever is making the current request.
 #[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
 pub struct CurrentUser {
-    email: String,
+    pub id: i64,
 }
 
 #[cfg(feature = "ssr")]
@@ -504,11 +1206,38 @@ impl FromRequest for CurrentUser {
         req: &actix_web::HttpRequest,
         pl: &mut actix_web::dev::Payload,
     ) -> Self::Future {
+        // API clients send `Authorization: Token <jwt>` instead of a cookie.
+        let token = req
+            .headers()
+            .get(actix_web::http::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Token "))
+            .map(|token| token.trim().to_string());
+        let tokens = req.app_data::<actix_web::web::Data<jwt::Tokens>>().cloned();
+        let pool = req.app_data::<actix_web::web::Data<SqlitePool>>().cloned();
         let fut = actix_session::Session::from_request(req, pl);
         Box::pin(async move {
+            if let Some(token) = token {
+                let invalid = || actix_web::error::ErrorUnauthorized("invalid token");
+                let (id, version) = tokens
+                    .and_then(|tokens| tokens.verify(&token).ok())
+                    .ok_or_else(invalid)?;
+                let pool = pool.ok_or_else(|| {
+                    actix_web::error::ErrorInternalServerError("no database pool available")
+                })?;
+                // A token from before its user's last revocation is no good.
+                let user = user::find_by_id(&pool, id)
+                    .await
+                    .map_err(actix_web::error::ErrorInternalServerError)?;
+                return match user {
+                    Some(user) if user.token_version == version => Ok(CurrentUser { id }),
+                    _ => Err(invalid()),
+                };
+            }
+
             if let Ok(sessions) = fut.await {
-                if let Ok(Some(email)) = sessions.get("user_email") {
-                    return Ok(CurrentUser { email });
+                if let Ok(Some(id)) = sessions.get("user_id") {
+                    return Ok(CurrentUser { id });
                 }
             };
 
@@ -0,0 +1,1367 @@
+use crate::{
+    app::{
+        encode_query_value, FieldErrors, FieldErrorsProps, Header, HeaderProps, RequireLogin,
+        RequireLoginProps,
+    },
+    comment::{Comments, CommentsProps},
+    profile::{FollowButton, FollowButtonProps},
+    user::Profile,
+    validations::{Field, Validate},
+};
+use cfg_if::cfg_if;
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ssr")]
+use crate::{
+    app::{logged_in_user, require_user},
+    db,
+};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Article {
+    pub slug: String,
+    pub title: String,
+    pub description: String,
+    pub body: String,
+    pub tag_list: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub author: Profile,
+    /// Whether the current user favorited the article.
+    pub favorited: bool,
+    pub favorites_count: i64,
+}
+
+/// A page of articles, and whether someone is logged in to favorite them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArticleListing {
+    pub articles: Vec<Article>,
+    /// How many articles there are across all pages.
+    pub total: i64,
+    pub logged_in: bool,
+}
+
+/// How many articles a page of the feed shows.
+pub const ARTICLES_PER_PAGE: i64 = 10;
+
+/// What the article page needs beyond the article itself.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArticleDetail {
+    pub article: Article,
+    /// The body rendered from Markdown and sanitized on the server.
+    pub body_html: String,
+    /// Whether the current user wrote the article and so may change it.
+    pub can_edit: bool,
+    pub logged_in: bool,
+}
+
+/// The outcome of looking an article up by a slug from a URL.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArticleLookup {
+    Found(Box<ArticleDetail>),
+    /// The article was renamed, and now lives at this slug.
+    Moved(String),
+    NotFound,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Validate)]
+pub struct ArticleForm {
+    #[validate(required, trim, not_empty)]
+    pub title: Field<String>,
+    #[validate(required, trim, not_empty)]
+    pub description: Field<String>,
+    #[validate(required, not_empty)]
+    pub body: Field<String>,
+    pub tag_list: Vec<String>,
+    /// Set once the article has been saved.
+    pub slug: Option<String>,
+}
+
+impl ArticleForm {
+    pub fn new(title: String, description: String, body: String, tag_list: Vec<String>) -> Self {
+        ArticleForm {
+            tag_list,
+            ..ArticleForm::from_inputs(Some(title), Some(description), Some(body))
+        }
+    }
+}
+
+/// Turns a `created_at` style timestamp into "January 20, 2023".
+pub fn format_date(timestamp: &str) -> String {
+    const MONTHS: [&str; 12] = [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ];
+
+    let mut parts = timestamp.get(..10).unwrap_or_default().split('-');
+    let year = parts.next();
+    let month = parts
+        .next()
+        .and_then(|month| month.parse::<usize>().ok())
+        .and_then(|month| MONTHS.get(month.wrapping_sub(1)));
+    let day = parts.next().and_then(|day| day.parse::<u32>().ok());
+
+    match (year, month, day) {
+        (Some(year), Some(month), Some(day)) => format!("{} {}, {}", month, day, year),
+        _ => timestamp.to_string(),
+    }
+}
+
+/// How many tags the sidebar of the home page shows.
+pub const POPULAR_TAGS: i64 = 20;
+
+/// The home page feed of articles tagged `tag`.
+pub fn tag_href(tag: &str) -> String {
+    format!("/?tag={}", encode_query_value(tag))
+}
+
+/// Splits the comma separated tag input of the editor into clean tags.
+pub fn parse_tags(input: &str) -> Vec<String> {
+    clean_tags(input.split(','))
+}
+
+/// Trims `tags`, dropping blanks and repeats but keeping the order.
+pub fn clean_tags<'a>(tags: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut cleaned: Vec<String> = vec![];
+    for tag in tags
+        .into_iter()
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+    {
+        if !cleaned.iter().any(|t| t == tag) {
+            cleaned.push(tag.to_string());
+        }
+    }
+    cleaned
+}
+
+cfg_if! {
+if #[cfg(feature = "ssr")] {
+    use sqlx::SqlitePool;
+
+    #[derive(sqlx::FromRow, Debug, Clone)]
+    pub struct ArticleRow {
+        pub id: i64,
+        pub slug: String,
+        pub title: String,
+        pub description: String,
+        pub body: String,
+        /// JSON array, as built by `json_group_array`.
+        pub tag_list: String,
+        pub created_at: String,
+        pub updated_at: String,
+        pub author_id: i64,
+        pub author_username: String,
+        pub author_bio: Option<String>,
+        pub author_image: Option<String>,
+        pub author_following: bool,
+        pub favorited: bool,
+        pub favorites_count: i64,
+    }
+
+    impl From<ArticleRow> for Article {
+        fn from(row: ArticleRow) -> Self {
+            Article {
+                slug: row.slug,
+                title: row.title,
+                description: row.description,
+                body: row.body,
+                tag_list: serde_json::from_str(&row.tag_list).unwrap_or_default(),
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+                author: Profile {
+                    username: row.author_username,
+                    bio: row.author_bio,
+                    image: row.author_image,
+                    following: row.author_following,
+                },
+                favorited: row.favorited,
+                favorites_count: row.favorites_count,
+            }
+        }
+    }
+
+    /// Binds `?1` to the id of the user reading, if any, for `favorited` and
+    /// `author_following`.
+    const SELECT_ARTICLES: &str = "
+        SELECT a.id, a.slug, a.title, a.description, a.body, a.created_at, a.updated_at,
+               a.author_id, u.username AS author_username, u.bio AS author_bio,
+               u.image AS author_image,
+               EXISTS (SELECT 1 FROM follows WHERE follower_id = ?1 AND followee_id = a.author_id)
+                 AS author_following,
+               (SELECT json_group_array(tag)
+                FROM (SELECT tag FROM article_tags WHERE article_id = a.id ORDER BY position)
+               ) AS tag_list,
+               EXISTS (SELECT 1 FROM favorites WHERE article_id = a.id AND user_id = ?1)
+                 AS favorited,
+               (SELECT COUNT(*) FROM favorites WHERE article_id = a.id) AS favorites_count
+        FROM articles a
+        JOIN users u ON u.id = a.author_id";
+
+    /// Narrows `list` down. Unset fields don't filter.
+    #[derive(Debug, Clone, Default)]
+    pub struct ArticleFilter {
+        pub tag: Option<String>,
+        pub author: Option<String>,
+        /// The username of someone who favorited the article.
+        pub favorited: Option<String>,
+        /// The id of a user following the author, for their feed.
+        pub followed_by: Option<i64>,
+    }
+
+    pub async fn find_by_slug(pool: &SqlitePool, slug: &str) -> Result<Option<ArticleRow>, sqlx::Error> {
+        find_by_slug_as(pool, slug, None).await
+    }
+
+    /// Like `find_by_slug`, with `favorited` and `author_following` filled in
+    /// for `viewer_id`.
+    pub async fn find_by_slug_as(
+        pool: &SqlitePool,
+        slug: &str,
+        viewer_id: Option<i64>,
+    ) -> Result<Option<ArticleRow>, sqlx::Error> {
+        sqlx::query_as::<_, ArticleRow>(&format!("{} WHERE a.slug = ?2", SELECT_ARTICLES))
+            .bind(viewer_id)
+            .bind(slug)
+            .fetch_optional(pool)
+            .await
+    }
+
+    /// Binds `?2` to `?5` to the fields of an `ArticleFilter`, in order.
+    const FILTER_ARTICLES: &str = "
+        WHERE (?2 IS NULL OR EXISTS (
+                SELECT 1 FROM article_tags t WHERE t.article_id = a.id AND t.tag = ?2))
+          AND (?3 IS NULL OR u.username = ?3 COLLATE NOCASE)
+          AND (?4 IS NULL OR EXISTS (
+                SELECT 1 FROM favorites f JOIN users fu ON fu.id = f.user_id
+                WHERE f.article_id = a.id AND fu.username = ?4 COLLATE NOCASE))
+          AND (?5 IS NULL OR EXISTS (
+                SELECT 1 FROM follows fo
+                WHERE fo.follower_id = ?5 AND fo.followee_id = a.author_id))";
+
+    /// A page of the articles matching `filter` as `viewer_id` sees them,
+    /// newest first.
+    pub async fn list(
+        pool: &SqlitePool,
+        filter: &ArticleFilter,
+        viewer_id: Option<i64>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ArticleRow>, sqlx::Error> {
+        sqlx::query_as::<_, ArticleRow>(&format!(
+            "{} {} ORDER BY a.created_at DESC, a.id DESC LIMIT ?6 OFFSET ?7",
+            SELECT_ARTICLES, FILTER_ARTICLES
+        ))
+        .bind(viewer_id)
+        .bind(&filter.tag)
+        .bind(&filter.author)
+        .bind(&filter.favorited)
+        .bind(filter.followed_by)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The `limit` tags used by the most articles, most used first.
+    pub async fn popular_tags(pool: &SqlitePool, limit: i64) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT tag FROM article_tags GROUP BY tag ORDER BY COUNT(*) DESC, tag LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Applies the RealWorld defaults of 20 articles from the start to a
+    /// requested `limit` and `offset`, and keeps both in range.
+    pub fn clamp_page(limit: Option<i64>, offset: Option<i64>) -> (i64, i64) {
+        const DEFAULT_LIMIT: i64 = 20;
+        const MAX_LIMIT: i64 = 100;
+
+        (
+            limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+            offset.unwrap_or(0).max(0),
+        )
+    }
+
+    /// How many articles match `filter` across all pages.
+    pub async fn count(pool: &SqlitePool, filter: &ArticleFilter) -> Result<i64, sqlx::Error> {
+        let (count,): (i64,) = sqlx::query_as(&format!(
+            "SELECT COUNT(*) FROM articles a JOIN users u ON u.id = a.author_id {}",
+            FILTER_ARTICLES
+        ))
+        // `?1` is the viewer in `list`, which doesn't change the count.
+        .bind(None::<i64>)
+        .bind(&filter.tag)
+        .bind(&filter.author)
+        .bind(&filter.favorited)
+        .bind(filter.followed_by)
+        .fetch_one(pool)
+        .await?;
+        Ok(count)
+    }
+
+    /// Sets the tags of `article_id`. They're cleaned up first, since the
+    /// JSON API takes them as they come.
+    async fn replace_tags(
+        conn: &mut sqlx::SqliteConnection,
+        article_id: i64,
+        tags: &[String],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM article_tags WHERE article_id = ?")
+            .bind(article_id)
+            .execute(&mut *conn)
+            .await?;
+        let tags = clean_tags(tags.iter().map(String::as_str));
+        for (position, tag) in tags.iter().enumerate() {
+            sqlx::query("INSERT INTO article_tags (article_id, tag, position) VALUES (?, ?, ?)")
+                .bind(article_id)
+                .bind(tag)
+                .bind(position as i64)
+                .execute(&mut *conn)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Saves a valid `form` as a new article by `author_id`, returning its slug.
+    pub async fn create(pool: &SqlitePool, author_id: i64, form: &ArticleForm) -> Result<String, sqlx::Error> {
+        let title = form.title.input.clone().unwrap_or_default();
+        let mut tx = pool.begin().await?;
+        let slug = unique_slug(&mut tx, &title, None).await?;
+        let (id,): (i64,) = sqlx::query_as(
+            "INSERT INTO articles (slug, title, description, body, author_id)
+             VALUES (?, ?, ?, ?, ?) RETURNING id",
+        )
+        .bind(&slug)
+        .bind(&title)
+        .bind(form.description.input.clone().unwrap_or_default())
+        .bind(form.body.input.clone().unwrap_or_default())
+        .bind(author_id)
+        .fetch_one(&mut tx)
+        .await?;
+        replace_tags(&mut tx, id, &form.tag_list).await?;
+        tx.commit().await?;
+        Ok(slug)
+    }
+
+    /// Overwrites `article` with a valid `form`, returning its slug. A new
+    /// title gets a new slug, and the old one is kept in `article_slugs` so
+    /// links to it still lead here.
+    pub async fn update(pool: &SqlitePool, article: &ArticleRow, form: &ArticleForm) -> Result<String, sqlx::Error> {
+        let title = form.title.input.clone().unwrap_or_default();
+        let mut tx = pool.begin().await?;
+        let slug = if title == article.title {
+            article.slug.clone()
+        } else {
+            unique_slug(&mut tx, &title, Some(article.id)).await?
+        };
+
+        if slug != article.slug {
+            sqlx::query("DELETE FROM article_slugs WHERE slug = ?")
+                .bind(&slug)
+                .execute(&mut tx)
+                .await?;
+            sqlx::query("INSERT INTO article_slugs (slug, article_id) VALUES (?, ?)")
+                .bind(&article.slug)
+                .bind(article.id)
+                .execute(&mut tx)
+                .await?;
+        }
+        sqlx::query(
+            "UPDATE articles
+             SET slug = ?, title = ?, description = ?, body = ?,
+                 updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+             WHERE id = ?",
+        )
+        .bind(&slug)
+        .bind(&title)
+        .bind(form.description.input.clone().unwrap_or_default())
+        .bind(form.body.input.clone().unwrap_or_default())
+        .bind(article.id)
+        .execute(&mut tx)
+        .await?;
+        replace_tags(&mut tx, article.id, &form.tag_list).await?;
+        tx.commit().await?;
+        Ok(slug)
+    }
+
+    /// Finds the current slug of the article that used to be at `slug`.
+    pub async fn find_renamed(pool: &SqlitePool, slug: &str) -> Result<Option<String>, sqlx::Error> {
+        let current: Option<(String,)> = sqlx::query_as(
+            "SELECT a.slug FROM article_slugs s JOIN articles a ON a.id = s.article_id
+             WHERE s.slug = ?",
+        )
+        .bind(slug)
+        .fetch_optional(pool)
+        .await?;
+        Ok(current.map(|(slug,)| slug))
+    }
+
+    pub async fn favorite(pool: &SqlitePool, user_id: i64, article_id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("INSERT OR IGNORE INTO favorites (user_id, article_id) VALUES (?, ?)")
+            .bind(user_id)
+            .bind(article_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn unfavorite(pool: &SqlitePool, user_id: i64, article_id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM favorites WHERE user_id = ? AND article_id = ?")
+            .bind(user_id)
+            .bind(article_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM articles WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    const MAX_SLUG_LENGTH: usize = 80;
+
+    /// Slugs that a route of the JSON API answers to instead, like
+    /// `GET /api/articles/feed`.
+    const RESERVED_SLUGS: &[&str] = &["feed"];
+
+    /// Makes a URL friendly slug out of `title`. Non-ASCII text is
+    /// transliterated first, so "Ünïcödé 日本" becomes "unicode-ri-ben".
+    pub fn slugify(title: &str) -> String {
+        let mut slug = String::new();
+        for word in deunicode::deunicode(title)
+            .to_lowercase()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+        {
+            if !slug.is_empty() && slug.len() + word.len() >= MAX_SLUG_LENGTH {
+                break;
+            }
+            if !slug.is_empty() {
+                slug.push('-');
+            }
+            slug.push_str(word);
+        }
+        slug.truncate(MAX_SLUG_LENGTH);
+
+        if slug.is_empty() {
+            "article".to_string()
+        } else {
+            slug
+        }
+    }
+
+    /// Slugifies `title` and, when that's reserved or already in use by
+    /// another article now or in the past, appends the first free `-2`,
+    /// `-3`, ... suffix.
+    async fn unique_slug(
+        conn: &mut sqlx::SqliteConnection,
+        title: &str,
+        article_id: Option<i64>,
+    ) -> Result<String, sqlx::Error> {
+        let base = slugify(title);
+        let mut candidate = base.clone();
+        let mut suffix = 1;
+        loop {
+            let (taken,): (bool,) = sqlx::query_as(
+                "SELECT EXISTS (SELECT 1 FROM articles WHERE slug = ?1 AND id IS NOT ?2)
+                     OR EXISTS (SELECT 1 FROM article_slugs WHERE slug = ?1 AND article_id IS NOT ?2)",
+            )
+            .bind(&candidate)
+            .bind(article_id)
+            .fetch_one(&mut *conn)
+            .await?;
+            if !taken && !RESERVED_SLUGS.contains(&candidate.as_str()) {
+                return Ok(candidate);
+            }
+            suffix += 1;
+            candidate = format!("{}-{}", base, suffix);
+        }
+    }
+
+    /// Fetches the article `slug` as `viewer_id` sees it, erroring when it
+    /// doesn't exist.
+    async fn find_for(pool: &SqlitePool, slug: &str, viewer_id: i64) -> Result<ArticleRow, ServerFnError> {
+        find_by_slug_as(pool, slug, Some(viewer_id))
+            .await
+            .map_err(db::server_error)?
+            .ok_or_else(|| ServerFnError::ServerError("article not found".to_string()))
+    }
+
+    /// Fetches the article `slug` when `author_id` wrote it. Errors when it
+    /// doesn't exist or belongs to someone else.
+    pub async fn find_own(
+        pool: &SqlitePool,
+        slug: &str,
+        author_id: i64,
+    ) -> Result<ArticleRow, ServerFnError> {
+        match find_by_slug(pool, slug).await.map_err(db::server_error)? {
+            Some(article) if article.author_id == author_id => Ok(article),
+            Some(_) => Err(ServerFnError::ServerError(
+                "only the author can change this article".to_string(),
+            )),
+            None => Err(ServerFnError::ServerError("article not found".to_string())),
+        }
+    }
+
+    /// Creates the article or, given `slug`, updates it. Shared by the server
+    /// functions and the JSON API.
+    pub async fn save(
+        pool: &SqlitePool,
+        author_id: i64,
+        slug: Option<&str>,
+        mut form: ArticleForm,
+    ) -> Result<ArticleForm, ServerFnError> {
+        if !form.is_valid() {
+            return Ok(form);
+        }
+
+        match slug {
+            Some(slug) => {
+                let article = find_own(pool, slug, author_id).await?;
+                let slug = update(pool, &article, &form).await.map_err(db::server_error)?;
+                form.slug = Some(slug);
+            }
+            None => {
+                let slug = create(pool, author_id, &form).await.map_err(db::server_error)?;
+                form.slug = Some(slug);
+            }
+        }
+
+        Ok(form)
+    }
+}
+}
+
+#[server(CreateArticle, "/api")]
+pub async fn create_article(
+    cx: Scope,
+    title: String,
+    description: String,
+    body: String,
+    tag_list: String,
+) -> Result<ArticleForm, ServerFnError> {
+    let pool = db::pool(cx)?;
+    let user = require_user(cx).await?;
+    let form = ArticleForm::new(title, description, body, parse_tags(&tag_list));
+
+    save(&pool, user.id, None, form).await
+}
+
+#[server(UpdateArticle, "/api")]
+pub async fn update_article(
+    cx: Scope,
+    slug: String,
+    title: String,
+    description: String,
+    body: String,
+    tag_list: String,
+) -> Result<ArticleForm, ServerFnError> {
+    let pool = db::pool(cx)?;
+    let user = require_user(cx).await?;
+    let form = ArticleForm::new(title, description, body, parse_tags(&tag_list));
+
+    save(&pool, user.id, Some(&slug), form).await
+}
+
+#[server(DeleteArticle, "/api")]
+pub async fn delete_article(cx: Scope, slug: String) -> Result<(), ServerFnError> {
+    let pool = db::pool(cx)?;
+    let user = require_user(cx).await?;
+    let article = find_own(&pool, &slug, user.id).await?;
+
+    delete(&pool, article.id).await.map_err(db::server_error)
+}
+
+/// The article `slug` to fill the editor with. Errors when someone other
+/// than its author asks, so they find out before changing anything.
+#[server(GetArticle, "/api")]
+pub async fn get_article(cx: Scope, slug: String) -> Result<Option<Article>, ServerFnError> {
+    let pool = db::pool(cx)?;
+    let user = require_user(cx).await?;
+    let article = match find_by_slug_as(&pool, &slug, Some(user.id))
+        .await
+        .map_err(db::server_error)?
+    {
+        Some(article) => article,
+        None => return Ok(None),
+    };
+    if article.author_id != user.id {
+        return Err(ServerFnError::ServerError(
+            "only the author can change this article".to_string(),
+        ));
+    }
+
+    Ok(Some(article.into()))
+}
+
+#[server(GetArticlePage, "/api")]
+pub async fn get_article_page(cx: Scope, slug: String) -> Result<ArticleLookup, ServerFnError> {
+    let pool = db::pool(cx)?;
+    let user = logged_in_user(cx).await?;
+    let viewer_id = user.as_ref().map(|user| user.id);
+    let article = match find_by_slug_as(&pool, &slug, viewer_id)
+        .await
+        .map_err(db::server_error)?
+    {
+        Some(article) => article,
+        None => {
+            return match find_renamed(&pool, &slug).await.map_err(db::server_error)? {
+                Some(current) => {
+                    crate::app::redirect_permanently(cx, &format!("/article/{}", current));
+                    Ok(ArticleLookup::Moved(current))
+                }
+                None => Ok(ArticleLookup::NotFound),
+            };
+        }
+    };
+
+    let can_edit = user
+        .as_ref()
+        .map(|user| user.id == article.author_id)
+        .unwrap_or(false);
+
+    Ok(ArticleLookup::Found(Box::new(ArticleDetail {
+        body_html: crate::markdown::to_safe_html(&article.body),
+        can_edit,
+        logged_in: user.is_some(),
+        article: article.into(),
+    })))
+}
+
+#[server(ListArticles, "/api")]
+pub async fn list_articles(
+    cx: Scope,
+    tag: Option<String>,
+    author: Option<String>,
+    favorited: Option<String>,
+    feed: bool,
+    limit: i64,
+    offset: i64,
+) -> Result<ArticleListing, ServerFnError> {
+    let pool = db::pool(cx)?;
+    let viewer_id = logged_in_user(cx).await?.map(|user| user.id);
+    if feed && viewer_id.is_none() {
+        return Err(ServerFnError::ServerError("not logged in".to_string()));
+    }
+    let filter = ArticleFilter {
+        tag,
+        author,
+        favorited,
+        followed_by: viewer_id.filter(|_| feed),
+    };
+    let (limit, offset) = clamp_page(Some(limit), Some(offset));
+    let articles = list(&pool, &filter, viewer_id, limit, offset)
+        .await
+        .map_err(db::server_error)?;
+    let total = count(&pool, &filter).await.map_err(db::server_error)?;
+
+    Ok(ArticleListing {
+        articles: articles.into_iter().map(Article::from).collect(),
+        total,
+        logged_in: viewer_id.is_some(),
+    })
+}
+
+#[server(GetTags, "/api")]
+pub async fn get_tags(cx: Scope) -> Result<Vec<String>, ServerFnError> {
+    let pool = db::pool(cx)?;
+    popular_tags(&pool, POPULAR_TAGS)
+        .await
+        .map_err(db::server_error)
+}
+
+#[server(FavoriteArticle, "/api")]
+pub async fn favorite_article(cx: Scope, slug: String) -> Result<Article, ServerFnError> {
+    let pool = db::pool(cx)?;
+    let user = require_user(cx).await?;
+    let article = find_for(&pool, &slug, user.id).await?;
+    favorite(&pool, user.id, article.id)
+        .await
+        .map_err(db::server_error)?;
+
+    find_for(&pool, &slug, user.id).await.map(Article::from)
+}
+
+#[server(UnfavoriteArticle, "/api")]
+pub async fn unfavorite_article(cx: Scope, slug: String) -> Result<Article, ServerFnError> {
+    let pool = db::pool(cx)?;
+    let user = require_user(cx).await?;
+    let article = find_for(&pool, &slug, user.id).await?;
+    unfavorite(&pool, user.id, article.id)
+        .await
+        .map_err(db::server_error)?;
+
+    find_for(&pool, &slug, user.id).await.map(Article::from)
+}
+
+/// `/editor` for a new article and `/editor/:slug` to change an existing one.
+#[component]
+pub fn EditorPage(cx: Scope) -> impl IntoView {
+    let params = use_params_map(cx);
+    let slug = move || params.with(|params| params.get("slug").cloned());
+
+    view! {cx,
+      <Header/>
+      <div class="editor-page">
+        <div class="container page">
+          <div class="row">
+            <div class="col-md-10 offset-md-1 col-xs-12">
+              <RequireLogin>
+                {move || match slug() {
+                    Some(slug) => view! {cx, <EditArticle slug=slug/>}.into_view(cx),
+                    None => view! {cx, <Editor article=None/>}.into_view(cx),
+                }}
+              </RequireLogin>
+            </div>
+          </div>
+        </div>
+      </div>
+    }
+}
+
+/// The editor, filled in with the article `slug`.
+#[component]
+fn EditArticle(cx: Scope, slug: String) -> impl IntoView {
+    let article = create_resource(cx, move || slug.clone(), move |slug| get_article(cx, slug));
+
+    view! {cx,
+      <Suspense fallback=move || view! {cx, <p>"Loading..."</p>}>
+        {move || article.read().map(|article| match article {
+            Ok(Some(article)) => view! {cx, <Editor article=Some(article)/>}.into_view(cx),
+            Ok(None) => view! {cx, <p>"This article doesn't exist."</p>}.into_view(cx),
+            Err(e) => view! {cx, <ServerError error=e/>}.into_view(cx),
+        })}
+      </Suspense>
+    }
+}
+
+/// A server function's failure, like not being the author of an article.
+#[component]
+fn ServerError(cx: Scope, error: ServerFnError) -> impl IntoView {
+    view! {cx,
+      <ul class="error-messages">
+        <li>{error.to_string()}</li>
+      </ul>
+    }
+}
+
+#[component]
+fn Editor(cx: Scope, article: Option<Article>) -> impl IntoView {
+    let editing = article.as_ref().map(|article| article.slug.clone());
+    let article = article.unwrap_or_default();
+    let create_action = create_server_action::<CreateArticle>(cx);
+    let update_action = create_server_action::<UpdateArticle>(cx);
+
+    let (title, set_title) = create_signal(cx, article.title);
+    let (description, set_description) = create_signal(cx, article.description);
+    let (body, set_body) = create_signal(cx, article.body);
+    let (tags, set_tags) = create_signal(cx, article.tag_list);
+    let (tag_input, set_tag_input) = create_signal(cx, String::new());
+    // Errors only show up once there has been something to validate.
+    let (touched, set_touched) = create_signal(cx, false);
+
+    let is_new = editing.is_none();
+    let pending = move || create_action.pending().get() || update_action.pending().get();
+    let result = move || {
+        if is_new {
+            create_action.value().get()
+        } else {
+            update_action.value().get()
+        }
+    };
+    let live_form =
+        move || ArticleForm::new(title.get(), description.get(), body.get(), tags.get());
+    // Live errors come from running the same validation as the server. Its
+    // answer carries the errors of what was submitted, which can differ from
+    // what's typed now, so both are shown. Failures that aren't about a field
+    // come back as an `Err` instead, see `server_error`.
+    let field_errors = move |pick: fn(ArticleForm) -> Field<String>| {
+        let mut errors = if touched.get() {
+            pick(live_form()).errors
+        } else {
+            vec![]
+        };
+        if let Some(Ok(form)) = result() {
+            for error in pick(form).errors {
+                if !errors.contains(&error) {
+                    errors.push(error);
+                }
+            }
+        }
+        errors
+    };
+    let server_error = move || match result() {
+        Some(Err(error)) => Some(view! {cx, <ServerError error=error/>}),
+        _ => None,
+    };
+
+    let add_tags = move |input: String| {
+        set_tags.update(|tags| {
+            for tag in parse_tags(&input) {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+        });
+        set_tag_input.set(String::new());
+    };
+    // What's still typed in the tag input counts too, as it would once added.
+    let tag_list = move || {
+        let mut tag_list = tags.get();
+        tag_list.extend(parse_tags(&tag_input.get()));
+        tag_list.join(",")
+    };
+
+    create_effect(cx, move |_| {
+        if let Some(Ok(ArticleForm {
+            slug: Some(slug), ..
+        })) = result()
+        {
+            let nav = use_navigate(cx);
+            let _ = nav(&format!("/article/{}", slug), Default::default());
+        }
+    });
+
+    let fields = move || {
+        view! {cx,
+            <fieldset disabled=pending>
+              <fieldset class="form-group">
+                <FieldErrors errors=move || field_errors(|form| form.title)/>
+                <input
+                  type="text"
+                  class="form-control form-control-lg"
+                  placeholder="Article Title"
+                  name="title"
+                  prop:value=title
+                  on:input=move |ev| {
+                      set_touched.set(true);
+                      set_title.set(event_target_value(&ev));
+                  }
+                />
+              </fieldset>
+              <fieldset class="form-group">
+                <FieldErrors errors=move || field_errors(|form| form.description)/>
+                <input
+                  type="text"
+                  class="form-control"
+                  placeholder="What's this article about?"
+                  name="description"
+                  prop:value=description
+                  on:input=move |ev| {
+                      set_touched.set(true);
+                      set_description.set(event_target_value(&ev));
+                  }
+                />
+              </fieldset>
+              <fieldset class="form-group">
+                <FieldErrors errors=move || field_errors(|form| form.body)/>
+                <textarea
+                  class="form-control"
+                  rows="8"
+                  placeholder="Write your article (in markdown)"
+                  name="body"
+                  prop:value=body
+                  on:input=move |ev| {
+                      set_touched.set(true);
+                      set_body.set(event_target_value(&ev));
+                  }
+                ></textarea>
+              </fieldset>
+              <fieldset class="form-group">
+                <input
+                  type="text"
+                  class="form-control"
+                  placeholder="Enter tags"
+                  prop:value=tag_input
+                  on:input=move |ev| set_tag_input.set(event_target_value(&ev))
+                  on:keydown=move |ev| {
+                      // Enter adds the tag rather than submitting the article.
+                      if ev.key() == "Enter" {
+                          ev.prevent_default();
+                          add_tags(tag_input.get());
+                      }
+                  }
+                />
+                <input type="hidden" name="tag_list" prop:value=tag_list/>
+                <div class="tag-list">
+                  <For
+                    each=move || tags.get()
+                    key=|tag| tag.clone()
+                    view=move |tag: String| {
+                        let removed = tag.clone();
+                        view! {cx,
+                          <span class="tag-default tag-pill">
+                            <i
+                              class="ion-close-round"
+                              on:click=move |_| set_tags.update(|tags| tags.retain(|t| *t != removed))
+                            ></i>
+                            {tag}
+                          </span>
+                        }
+                    }
+                  />
+                </div>
+              </fieldset>
+              <button class="btn btn-lg pull-xs-right btn-primary" type="submit">
+                "Publish Article"
+              </button>
+            </fieldset>
+        }
+    };
+
+    view! {cx,
+      {server_error}
+      {match editing {
+          Some(slug) => view! {cx,
+            <ActionForm action=update_action>
+              <input type="hidden" name="slug" value=slug/>
+              {fields}
+            </ActionForm>
+          }
+          .into_view(cx),
+          None => view! {cx,
+            <ActionForm action=create_action>
+              {fields}
+            </ActionForm>
+          }
+          .into_view(cx),
+      }}
+    }
+}
+
+/// `/article/:slug`
+#[component]
+pub fn ArticlePage(cx: Scope) -> impl IntoView {
+    let params = use_params_map(cx);
+    let slug = move || params.with(|params| params.get("slug").cloned().unwrap_or_default());
+    let detail = create_resource(cx, slug, move |slug| get_article_page(cx, slug));
+
+    view! {cx,
+      <Header/>
+      <div class="article-page">
+        <Suspense fallback=move || view! {cx, <p>"Loading..."</p>}>
+          {move || detail.read().map(|detail| match detail {
+              Ok(ArticleLookup::Found(detail)) => view! {cx, <ArticleView detail=*detail/>}.into_view(cx),
+              Ok(ArticleLookup::Moved(current)) => {
+                  // Only reached when the server's 301 didn't apply, like
+                  // after following an old link inside the app.
+                  let nav = use_navigate(cx);
+                  let _ = nav(
+                      &format!("/article/{}", current),
+                      NavigateOptions { replace: true, ..Default::default() },
+                  );
+                  view! {cx, <p>"Loading..."</p>}.into_view(cx)
+              }
+              _ => view! {cx,
+                <div class="container page">
+                  <p>"This article doesn't exist."</p>
+                </div>
+              }.into_view(cx),
+          })}
+        </Suspense>
+      </div>
+    }
+}
+
+#[component]
+fn ArticleView(cx: Scope, detail: ArticleDetail) -> impl IntoView {
+    let ArticleDetail {
+        article,
+        body_html,
+        can_edit,
+        logged_in,
+    } = detail;
+    let tags = article.tag_list.clone();
+    let title = article.title.clone();
+    let slug = article.slug.clone();
+    // Both copies of the meta show the same buttons, so they share their state.
+    let following = create_rw_signal(cx, article.author.following);
+    let favorite = FavoriteState::new(cx, article.favorited, article.favorites_count);
+
+    view! {cx,
+      <div class="banner">
+        <div class="container">
+          <h1>{title}</h1>
+          <ArticleMeta
+            article=article.clone()
+            following=following
+            favorite=favorite
+            can_edit=can_edit
+            logged_in=logged_in
+          />
+        </div>
+      </div>
+
+      <div class="container page">
+        <div class="row article-content">
+          <div class="col-md-12">
+            <div inner_html=body_html></div>
+            <ul class="tag-list">
+              {tags
+                  .into_iter()
+                  .map(|tag| view! {cx, <li class="tag-default tag-pill tag-outline">{tag}</li>})
+                  .collect::<Vec<_>>()}
+            </ul>
+          </div>
+        </div>
+
+        <hr/>
+
+        <div class="article-actions">
+          <ArticleMeta
+            article=article
+            following=following
+            favorite=favorite
+            can_edit=can_edit
+            logged_in=logged_in
+          />
+        </div>
+
+        <Comments slug=slug/>
+      </div>
+    }
+}
+
+/// The author, date and action buttons shown above and below an article.
+#[component]
+fn ArticleMeta(
+    cx: Scope,
+    article: Article,
+    following: RwSignal<bool>,
+    favorite: FavoriteState,
+    can_edit: bool,
+    logged_in: bool,
+) -> impl IntoView {
+    let delete_action = create_server_action::<DeleteArticle>(cx);
+    create_effect(cx, move |_| {
+        if let Some(Ok(_)) = delete_action.value().get() {
+            let nav = use_navigate(cx);
+            let _ = nav("/", Default::default());
+        }
+    });
+
+    let author = article.author;
+    let profile_link = format!("/profile/{}", author.username);
+    let image = author.image.clone().unwrap_or_default();
+    let date = format_date(&article.created_at);
+    let slug = article.slug;
+
+    let actions = if can_edit {
+        view! {cx,
+          <span>
+            <A class="btn btn-sm btn-outline-secondary" href=format!("/editor/{}", slug)>
+              <i class="ion-edit"></i>" Edit Article"
+            </A>
+            " "
+            <ActionForm action=delete_action>
+              <input type="hidden" name="slug" value=slug/>
+              <button class="btn btn-sm btn-outline-danger">
+                <i class="ion-trash-a"></i>" Delete Article"
+              </button>
+            </ActionForm>
+          </span>
+        }
+    } else {
+        view! {cx,
+          <span>
+            <FollowButton
+              username=author.username.clone()
+              following=following
+              logged_in=logged_in
+            />
+            " "
+            <FavoriteButton slug=slug favorite=favorite logged_in=logged_in compact=false/>
+          </span>
+        }
+    };
+
+    view! {cx,
+      <div class="article-meta">
+        <A href=profile_link.clone()><img src=image/></A>
+        <div class="info">
+          <A href=profile_link class="author">{author.username}</A>
+          <span class="date">{date}</span>
+        </div>
+        {actions}
+      </div>
+    }
+}
+
+/// Reads the 1-based `?page=` of the current URL.
+pub fn use_page(cx: Scope) -> impl Fn() -> i64 + Copy {
+    let query = use_query_map(cx);
+    move || {
+        query.with(|query| {
+            query
+                .get("page")
+                .and_then(|page| page.parse::<i64>().ok())
+                .filter(|page| *page > 0)
+                .unwrap_or(1)
+        })
+    }
+}
+
+/// The 1-based `page` of the articles matching the given filters, newest
+/// first, with links to the other pages from `page_href`. With `feed`, only
+/// articles by authors the current user follows.
+#[component]
+pub fn ArticleList<F>(
+    cx: Scope,
+    tag: Option<String>,
+    author: Option<String>,
+    favorited: Option<String>,
+    feed: bool,
+    page: i64,
+    page_href: F,
+) -> impl IntoView
+where
+    F: Fn(i64) -> String + Clone + 'static,
+{
+    let articles = create_resource(
+        cx,
+        || (),
+        move |_| {
+            list_articles(
+                cx,
+                tag.clone(),
+                author.clone(),
+                favorited.clone(),
+                feed,
+                ARTICLES_PER_PAGE,
+                (page - 1) * ARTICLES_PER_PAGE,
+            )
+        },
+    );
+
+    view! {cx,
+      <Suspense fallback=move || view! {cx, <div class="article-preview">"Loading articles..."</div>}>
+        {move || articles.read().map(|listing| match listing {
+            Ok(listing) if listing.articles.is_empty() => view! {cx,
+              <div class="article-preview">"No articles are here... yet."</div>
+            }
+            .into_view(cx),
+            Ok(ArticleListing { articles, total, logged_in }) => view! {cx,
+              {articles
+                  .into_iter()
+                  .map(|article| view! {cx, <ArticlePreview article=article logged_in=logged_in/>})
+                  .collect::<Vec<_>>()}
+              <Pagination page=page total=total page_href=page_href.clone()/>
+            }
+            .into_view(cx),
+            Err(_) => view! {cx,
+              <div class="article-preview">"Articles couldn't be loaded."</div>
+            }
+            .into_view(cx),
+        })}
+      </Suspense>
+    }
+}
+
+/// Links to every page of a list of `total` articles, with `page` marked.
+#[component]
+fn Pagination<F>(cx: Scope, page: i64, total: i64, page_href: F) -> impl IntoView
+where
+    F: Fn(i64) -> String + 'static,
+{
+    let pages = (total + ARTICLES_PER_PAGE - 1) / ARTICLES_PER_PAGE;
+    if pages <= 1 {
+        return ().into_view(cx);
+    }
+
+    view! {cx,
+      <nav>
+        <ul class="pagination">
+          {(1..=pages)
+              .map(|number| view! {cx,
+                <li class=if number == page { "page-item active" } else { "page-item" }>
+                  <A class="page-link" href=page_href(number)>{number}</A>
+                </li>
+              })
+              .collect::<Vec<_>>()}
+        </ul>
+      </nav>
+    }
+    .into_view(cx)
+}
+
+/// One entry in a list of articles.
+#[component]
+pub fn ArticlePreview(cx: Scope, article: Article, logged_in: bool) -> impl IntoView {
+    let profile_link = format!("/profile/{}", article.author.username);
+    let date = format_date(&article.created_at);
+    let favorite = view! {cx,
+      <FavoriteButton
+        slug=article.slug.clone()
+        favorite=FavoriteState::new(cx, article.favorited, article.favorites_count)
+        logged_in=logged_in
+        compact=true
+      />
+    };
+
+    view! {cx,
+      <div class="article-preview">
+        <div class="article-meta">
+          <A href=profile_link.clone()><img src=article.author.image.unwrap_or_default()/></A>
+          <div class="info">
+            <A href=profile_link class="author">{article.author.username}</A>
+            <span class="date">{date}</span>
+          </div>
+          {favorite}
+        </div>
+        <A href=format!("/article/{}", article.slug) class="preview-link">
+          <h1>{article.title}</h1>
+          <p>{article.description}</p>
+          <span>"Read more..."</span>
+          <ul class="tag-list">
+            {article
+                .tag_list
+                .into_iter()
+                .map(|tag| view! {cx, <li class="tag-default tag-pill tag-outline">{tag}</li>})
+                .collect::<Vec<_>>()}
+          </ul>
+        </A>
+      </div>
+    }
+}
+
+/// Whether the current user favorites an article and how many people do,
+/// for every `FavoriteButton` showing that article.
+#[derive(Clone, Copy)]
+pub struct FavoriteState {
+    /// What the server last confirmed.
+    confirmed: RwSignal<(bool, i64)>,
+    /// What is shown meanwhile.
+    shown: RwSignal<(bool, i64)>,
+}
+
+impl FavoriteState {
+    pub fn new(cx: Scope, favorited: bool, favorites_count: i64) -> Self {
+        FavoriteState {
+            confirmed: create_rw_signal(cx, (favorited, favorites_count)),
+            shown: create_rw_signal(cx, (favorited, favorites_count)),
+        }
+    }
+}
+
+/// Toggles whether the current user favorites the article `slug`. The button
+/// flips right away and flips back if the server disagrees. Logged out
+/// visitors are sent to log in instead. `compact` is the style used in
+/// article lists, which only shows the count.
+#[component]
+pub fn FavoriteButton(
+    cx: Scope,
+    slug: String,
+    favorite: FavoriteState,
+    logged_in: bool,
+    compact: bool,
+) -> impl IntoView {
+    let favorite_action = create_server_action::<FavoriteArticle>(cx);
+    let unfavorite_action = create_server_action::<UnfavoriteArticle>(cx);
+    let FavoriteState { confirmed, shown } = favorite;
+    let settle = move |result: Option<Result<Article, ServerFnError>>| match result {
+        Some(Ok(article)) => {
+            confirmed.set((article.favorited, article.favorites_count));
+            shown.set((article.favorited, article.favorites_count));
+        }
+        Some(Err(_)) => shown.set(confirmed.get()),
+        None => {}
+    };
+    create_effect(cx, move |_| settle(favorite_action.value().get()));
+    create_effect(cx, move |_| settle(unfavorite_action.value().get()));
+
+    let pending = move || favorite_action.pending().get() || unfavorite_action.pending().get();
+    let toggle = move |_| {
+        if !logged_in {
+            let nav = use_navigate(cx);
+            let _ = nav("/login", Default::default());
+            return;
+        }
+        let (favorited, count) = shown.get();
+        let slug = slug.clone();
+        if favorited {
+            shown.set((false, count - 1));
+            unfavorite_action.dispatch(UnfavoriteArticle { slug });
+        } else {
+            shown.set((true, count + 1));
+            favorite_action.dispatch(FavoriteArticle { slug });
+        }
+    };
+    let class = move || {
+        let style = if shown.get().0 {
+            "btn btn-sm btn-primary"
+        } else {
+            "btn btn-sm btn-outline-primary"
+        };
+        if compact {
+            format!("{} pull-xs-right", style)
+        } else {
+            style.to_string()
+        }
+    };
+    let label = move || {
+        let (favorited, count) = shown.get();
+        if compact {
+            format!(" {}", count)
+        } else if favorited {
+            format!(" Unfavorite Article ({})", count)
+        } else {
+            format!(" Favorite Article ({})", count)
+        }
+    };
+
+    view! {cx,
+      <button class=class disabled=pending on:click=toggle>
+        <i class="ion-heart"></i>
+        {label}
+      </button>
+    }
+}
+
+#[cfg(all(test, feature = "ssr"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugs_are_transliterated() {
+        assert_eq!(slugify("Ünïcödé 日本"), "unicode-ri-ben");
+        assert_eq!(slugify("Hello, World! It's 2023."), "hello-world-it-s-2023");
+        assert_eq!(slugify("  --Rust--  "), "rust");
+    }
+
+    #[test]
+    fn titles_without_letters_or_digits_fall_back() {
+        assert_eq!(slugify(""), "article");
+        assert_eq!(slugify("?!... --"), "article");
+    }
+
+    #[test]
+    fn long_titles_are_cut_between_words() {
+        let title = "word ".repeat(30);
+        let slug = slugify(&title);
+        assert_eq!(slug.len(), 79);
+        assert!(slug.split('-').all(|word| word == "word"));
+
+        // A single word longer than the limit is cut inside it.
+        assert_eq!(slugify(&"a".repeat(100)), "a".repeat(MAX_SLUG_LENGTH));
+    }
+}
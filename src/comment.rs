@@ -0,0 +1,790 @@
+use crate::{
+    app::{FieldErrors, FieldErrorsProps},
+    article::format_date,
+    user::Profile,
+    validations::{Field, Validate},
+};
+use cfg_if::cfg_if;
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[cfg(feature = "ssr")]
+use crate::{
+    app::{logged_in_user, require_user},
+    db,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    pub id: i64,
+    pub created_at: String,
+    pub updated_at: String,
+    pub body: String,
+    pub author: Profile,
+    /// The comment this one replies to.
+    pub parent_id: Option<i64>,
+    /// Whether the author changed the comment after posting it.
+    pub edited: bool,
+    /// Deleted comments keep their place in the thread, but not their body.
+    pub deleted: bool,
+}
+
+/// The comments of an article, and who is reading them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommentList {
+    pub comments: Vec<Comment>,
+    /// `None` when nobody is logged in, who then can't comment.
+    pub viewer: Option<Profile>,
+    /// The viewer's comments that are still within the edit window.
+    pub editable: Vec<i64>,
+}
+
+/// How many levels a thread can have, counting the top level comment.
+pub const MAX_DEPTH: usize = 4;
+
+/// How long after posting a comment its author can still change it.
+pub const EDIT_WINDOW_MINUTES: i64 = 15;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Validate)]
+pub struct CommentForm {
+    #[validate(required, trim, not_empty)]
+    pub body: Field<String>,
+    /// Set once the comment has been saved.
+    pub comment: Option<Comment>,
+}
+
+impl CommentForm {
+    pub fn new(body: String) -> Self {
+        CommentForm::from_inputs(Some(body))
+    }
+}
+
+/// Orders `comments` for display: newest threads first, each followed by its
+/// replies oldest first. Every comment comes paired with its depth.
+pub fn threaded(comments: Vec<Comment>) -> Vec<(usize, Comment)> {
+    let mut replies: HashMap<Option<i64>, Vec<Comment>> = HashMap::new();
+    for comment in comments {
+        replies.entry(comment.parent_id).or_default().push(comment);
+    }
+
+    let mut roots = replies.remove(&None).unwrap_or_default();
+    roots.sort_by_key(|comment| comment.id);
+    // Popping from the end yields the newest thread first.
+    let mut stack: Vec<(usize, Comment)> = roots.into_iter().map(|comment| (0, comment)).collect();
+    let mut ordered = vec![];
+    while let Some((depth, comment)) = stack.pop() {
+        if let Some(mut children) = replies.remove(&Some(comment.id)) {
+            children.sort_by_key(|child| std::cmp::Reverse(child.id));
+            stack.extend(children.into_iter().map(|child| (depth + 1, child)));
+        }
+        ordered.push((depth, comment));
+    }
+    ordered
+}
+
+cfg_if! {
+if #[cfg(feature = "ssr")] {
+    use sqlx::SqlitePool;
+    use std::fmt;
+
+    /// Why a comment couldn't be saved.
+    #[derive(Debug)]
+    pub enum CommentError {
+        /// The comment replied to is missing, deleted or too deep.
+        CantReply(&'static str),
+        EditWindowClosed,
+        Database(sqlx::Error),
+    }
+
+    impl fmt::Display for CommentError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                CommentError::CantReply(reason) => write!(f, "{}", reason),
+                CommentError::EditWindowClosed => {
+                    write!(f, "this comment can no longer be edited")
+                }
+                CommentError::Database(e) => write!(f, "{}", e),
+            }
+        }
+    }
+
+    impl From<sqlx::Error> for CommentError {
+        fn from(e: sqlx::Error) -> Self {
+            CommentError::Database(e)
+        }
+    }
+
+    #[derive(sqlx::FromRow, Debug, Clone)]
+    pub struct CommentRow {
+        pub id: i64,
+        pub article_id: i64,
+        pub parent_id: Option<i64>,
+        pub depth: i64,
+        pub body: String,
+        pub created_at: String,
+        pub updated_at: String,
+        pub edited_at: Option<String>,
+        pub deleted_at: Option<String>,
+        pub author_id: i64,
+        pub author_username: String,
+        pub author_bio: Option<String>,
+        pub author_image: Option<String>,
+        pub author_following: bool,
+    }
+
+    impl From<CommentRow> for Comment {
+        fn from(row: CommentRow) -> Self {
+            Comment {
+                id: row.id,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+                body: row.body,
+                author: Profile {
+                    username: row.author_username,
+                    bio: row.author_bio,
+                    image: row.author_image,
+                    following: row.author_following,
+                },
+                parent_id: row.parent_id,
+                edited: row.edited_at.is_some(),
+                deleted: row.deleted_at.is_some(),
+            }
+        }
+    }
+
+    /// Binds `?1` to the id of the user reading, if any, for
+    /// `author_following`.
+    const SELECT_COMMENTS: &str = "
+        SELECT c.id, c.article_id, c.parent_id, c.depth, c.body, c.created_at, c.updated_at,
+               c.edited_at, c.deleted_at, c.author_id, u.username AS author_username,
+               u.bio AS author_bio, u.image AS author_image,
+               EXISTS (SELECT 1 FROM follows WHERE follower_id = ?1 AND followee_id = c.author_id)
+                 AS author_following
+        FROM comments c
+        JOIN users u ON u.id = c.author_id";
+
+    /// The `strftime` modifier for the start of the edit window.
+    fn edit_window_start() -> String {
+        format!("-{} minutes", EDIT_WINDOW_MINUTES)
+    }
+
+    /// The comment `id`, with its author not followed. It's looked up to check
+    /// who may change it, or to hand it back to the author who just saved it,
+    /// and nobody follows themselves.
+    pub async fn find(pool: &SqlitePool, id: i64) -> Result<Option<CommentRow>, sqlx::Error> {
+        sqlx::query_as::<_, CommentRow>(&format!("{} WHERE c.id = ?2", SELECT_COMMENTS))
+            .bind(None::<i64>)
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    /// The comments on `article_id` as `viewer_id` sees them, newest first.
+    pub async fn list(
+        pool: &SqlitePool,
+        article_id: i64,
+        viewer_id: Option<i64>,
+    ) -> Result<Vec<CommentRow>, sqlx::Error> {
+        sqlx::query_as::<_, CommentRow>(&format!(
+            "{} WHERE c.article_id = ?2 ORDER BY c.created_at DESC, c.id DESC",
+            SELECT_COMMENTS
+        ))
+        .bind(viewer_id)
+        .bind(article_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The comments `author_id` wrote on `article_id` that can still be edited.
+    pub async fn list_editable(
+        pool: &SqlitePool,
+        article_id: i64,
+        author_id: i64,
+    ) -> Result<Vec<i64>, sqlx::Error> {
+        let ids: Vec<(i64,)> = sqlx::query_as(
+            "SELECT id FROM comments
+             WHERE article_id = ? AND author_id = ? AND deleted_at IS NULL
+               AND created_at >= strftime('%Y-%m-%dT%H:%M:%fZ', 'now', ?)",
+        )
+        .bind(article_id)
+        .bind(author_id)
+        .bind(edit_window_start())
+        .fetch_all(pool)
+        .await?;
+        Ok(ids.into_iter().map(|(id,)| id).collect())
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        article_id: i64,
+        author_id: i64,
+        parent: Option<&CommentRow>,
+        body: &str,
+    ) -> Result<i64, sqlx::Error> {
+        let (id,): (i64,) = sqlx::query_as(
+            "INSERT INTO comments (article_id, author_id, parent_id, depth, body)
+             VALUES (?, ?, ?, ?, ?) RETURNING id",
+        )
+        .bind(article_id)
+        .bind(author_id)
+        .bind(parent.map(|parent| parent.id))
+        .bind(parent.map(|parent| parent.depth + 1).unwrap_or(0))
+        .bind(body)
+        .fetch_one(pool)
+        .await?;
+        Ok(id)
+    }
+
+    /// Replaces the body of the comment `id`, unless the edit window has
+    /// closed. Returns whether it was changed.
+    pub async fn edit(pool: &SqlitePool, id: i64, body: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE comments
+             SET body = ?,
+                 edited_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now'),
+                 updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+             WHERE id = ? AND deleted_at IS NULL
+               AND created_at >= strftime('%Y-%m-%dT%H:%M:%fZ', 'now', ?)",
+        )
+        .bind(body)
+        .bind(id)
+        .bind(edit_window_start())
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Blanks the comment `id` rather than removing it, so its replies stay
+    /// where they are.
+    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE comments
+             SET body = '',
+                 deleted_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now'),
+                 updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+             WHERE id = ?",
+        )
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Saves a valid `form` as a comment by `author_id` on `article_id`, or
+    /// as a reply to `parent_id` there. Shared by the server functions and
+    /// the JSON API.
+    pub async fn save(
+        pool: &SqlitePool,
+        article_id: i64,
+        author_id: i64,
+        parent_id: Option<i64>,
+        mut form: CommentForm,
+    ) -> Result<CommentForm, CommentError> {
+        if !form.is_valid() {
+            return Ok(form);
+        }
+
+        let parent = match parent_id {
+            Some(parent_id) => Some(find_reply_target(pool, article_id, parent_id).await?),
+            None => None,
+        };
+        let body = form.body.input.clone().unwrap_or_default();
+        let id = create(pool, article_id, author_id, parent.as_ref(), &body).await?;
+        form.comment = find(pool, id).await?.map(Comment::from);
+        Ok(form)
+    }
+
+    /// Changes the comment `id` to a valid `form`. Shared by the server
+    /// functions and the JSON API.
+    pub async fn save_edit(
+        pool: &SqlitePool,
+        id: i64,
+        mut form: CommentForm,
+    ) -> Result<CommentForm, CommentError> {
+        if !form.is_valid() {
+            return Ok(form);
+        }
+
+        let body = form.body.input.clone().unwrap_or_default();
+        if !edit(pool, id, &body).await? {
+            return Err(CommentError::EditWindowClosed);
+        }
+        form.comment = find(pool, id).await?.map(Comment::from);
+        Ok(form)
+    }
+
+    /// Fetches the comment `parent_id` on `article_id` when it can be
+    /// replied to.
+    async fn find_reply_target(
+        pool: &SqlitePool,
+        article_id: i64,
+        parent_id: i64,
+    ) -> Result<CommentRow, CommentError> {
+        match find(pool, parent_id).await? {
+            Some(parent) if parent.article_id != article_id => {
+                Err(CommentError::CantReply("comment not found"))
+            }
+            Some(parent) if parent.deleted_at.is_some() => {
+                Err(CommentError::CantReply("deleted comments can't be replied to"))
+            }
+            Some(parent) if parent.depth + 1 >= MAX_DEPTH as i64 => {
+                Err(CommentError::CantReply("this thread can't go any deeper"))
+            }
+            Some(parent) => Ok(parent),
+            None => Err(CommentError::CantReply("comment not found")),
+        }
+    }
+
+    /// Fetches the comment `id` on `article_id` when `author_id` wrote it.
+    /// Errors when it doesn't exist, is deleted or belongs to someone else.
+    pub async fn find_own(
+        pool: &SqlitePool,
+        article_id: i64,
+        id: i64,
+        author_id: i64,
+    ) -> Result<CommentRow, ServerFnError> {
+        match find(pool, id).await.map_err(db::server_error)? {
+            Some(comment) if comment.article_id != article_id || comment.deleted_at.is_some() => {
+                Err(ServerFnError::ServerError("comment not found".to_string()))
+            }
+            Some(comment) if comment.author_id == author_id => Ok(comment),
+            Some(_) => Err(ServerFnError::ServerError(
+                "only the author can change this comment".to_string(),
+            )),
+            None => Err(ServerFnError::ServerError("comment not found".to_string())),
+        }
+    }
+
+    async fn article_id(pool: &SqlitePool, slug: &str) -> Result<i64, ServerFnError> {
+        crate::article::find_by_slug(pool, slug)
+            .await
+            .map_err(db::server_error)?
+            .map(|article| article.id)
+            .ok_or_else(|| ServerFnError::ServerError("article not found".to_string()))
+    }
+}
+}
+
+#[server(AddComment, "/api")]
+pub async fn add_comment(
+    cx: Scope,
+    slug: String,
+    parent_id: Option<i64>,
+    body: String,
+) -> Result<CommentForm, ServerFnError> {
+    let pool = db::pool(cx)?;
+    let user = require_user(cx).await?;
+    let article_id = article_id(&pool, &slug).await?;
+
+    save(
+        &pool,
+        article_id,
+        user.id,
+        parent_id,
+        CommentForm::new(body),
+    )
+    .await
+    .map_err(db::server_error)
+}
+
+#[server(EditComment, "/api")]
+pub async fn edit_comment(
+    cx: Scope,
+    slug: String,
+    id: i64,
+    body: String,
+) -> Result<CommentForm, ServerFnError> {
+    let pool = db::pool(cx)?;
+    let user = require_user(cx).await?;
+    let article_id = article_id(&pool, &slug).await?;
+    let comment = find_own(&pool, article_id, id, user.id).await?;
+
+    save_edit(&pool, comment.id, CommentForm::new(body))
+        .await
+        .map_err(db::server_error)
+}
+
+#[server(ListComments, "/api")]
+pub async fn list_comments(cx: Scope, slug: String) -> Result<CommentList, ServerFnError> {
+    let pool = db::pool(cx)?;
+    let user = logged_in_user(cx).await?;
+    let article_id = article_id(&pool, &slug).await?;
+    let comments = list(&pool, article_id, user.as_ref().map(|user| user.id))
+        .await
+        .map_err(db::server_error)?;
+    let editable = match &user {
+        Some(user) => list_editable(&pool, article_id, user.id)
+            .await
+            .map_err(db::server_error)?,
+        None => vec![],
+    };
+
+    Ok(CommentList {
+        comments: comments.into_iter().map(Comment::from).collect(),
+        viewer: user.map(Profile::from),
+        editable,
+    })
+}
+
+#[server(DeleteComment, "/api")]
+pub async fn delete_comment(cx: Scope, slug: String, id: i64) -> Result<(), ServerFnError> {
+    let pool = db::pool(cx)?;
+    let user = require_user(cx).await?;
+    let article_id = article_id(&pool, &slug).await?;
+    let comment = find_own(&pool, article_id, id, user.id).await?;
+
+    delete(&pool, comment.id).await.map_err(db::server_error)
+}
+
+/// The comment form and the threads below an article.
+#[component]
+pub fn Comments(cx: Scope, slug: String) -> impl IntoView {
+    // Bumped whenever a comment is saved or deleted, to reload the threads.
+    let changed = create_rw_signal(cx, 0);
+    let comments = {
+        let slug = slug.clone();
+        create_resource(
+            cx,
+            move || changed.get(),
+            move |_| list_comments(cx, slug.clone()),
+        )
+    };
+
+    view! {cx,
+      <div class="row">
+        <div class="col-xs-12 col-md-8 offset-md-2">
+          <Suspense fallback=move || view! {cx, <p>"Loading comments..."</p>}>
+            {
+                let slug = slug.clone();
+                move || comments.read().map(|comments| match comments {
+                    Ok(CommentList { comments, viewer, editable }) => {
+                        let form = match viewer.clone() {
+                            Some(viewer) => view! {cx,
+                              <CommentEditor slug=slug.clone() parent_id=None viewer=viewer changed=changed/>
+                            }
+                            .into_view(cx),
+                            None => view! {cx,
+                              <p>
+                                <A href="/login">"Sign in"</A>" or "<A href="/register">"sign up"</A>
+                                " to add comments on this article."
+                              </p>
+                            }
+                            .into_view(cx),
+                        };
+
+                        view! {cx,
+                          {form}
+                          {threaded(comments)
+                              .into_iter()
+                              .map(|(depth, comment)| {
+                                  let can_edit = editable.contains(&comment.id);
+                                  view! {cx,
+                                    <CommentCard
+                                      slug=slug.clone()
+                                      depth=depth
+                                      comment=comment
+                                      viewer=viewer.clone()
+                                      can_edit=can_edit
+                                      changed=changed
+                                    />
+                                  }
+                              })
+                              .collect::<Vec<_>>()}
+                        }
+                        .into_view(cx)
+                    }
+                    Err(_) => view! {cx, <p>"Comments couldn't be loaded."</p>}.into_view(cx),
+                })
+            }
+          </Suspense>
+        </div>
+      </div>
+    }
+}
+
+/// Posts a new comment, or a reply to `parent_id`. The comment shows up
+/// right away while it is being posted; a failure drops it again and leaves
+/// the text in the form to try once more.
+#[component]
+fn CommentEditor(
+    cx: Scope,
+    slug: String,
+    parent_id: Option<i64>,
+    viewer: Profile,
+    changed: RwSignal<usize>,
+) -> impl IntoView {
+    let add_action = create_server_action::<AddComment>(cx);
+    let (body, set_body) = create_signal(cx, String::new());
+    let pending_body = move || {
+        if add_action.pending().get() {
+            add_action.input().get().map(|input| input.body)
+        } else {
+            None
+        }
+    };
+    let failed = move || matches!(add_action.value().get(), Some(Err(_)));
+    let errors = move || match add_action.value().get() {
+        Some(Ok(form)) => form.body.errors,
+        _ => vec![],
+    };
+    create_effect(cx, move |_| {
+        if let Some(Ok(CommentForm {
+            comment: Some(_), ..
+        })) = add_action.value().get()
+        {
+            set_body.set(String::new());
+            changed.update(|n| *n += 1);
+        }
+    });
+
+    let image = viewer.image.unwrap_or_default();
+    let username = viewer.username;
+    let placeholder = if parent_id.is_some() {
+        "Write a reply..."
+    } else {
+        "Write a comment..."
+    };
+
+    view! {cx,
+      <ActionForm action=add_action>
+        <input type="hidden" name="slug" value=slug/>
+        {parent_id.map(|id| view! {cx, <input type="hidden" name="parent_id" value=id.to_string()/>})}
+        <div class="card comment-form">
+          <div class="card-block">
+            <FieldErrors errors=errors/>
+            <Show when=failed fallback=move |_| {}>
+              <ul class="error-messages">
+                <li>"Your comment couldn't be posted, please try again."</li>
+              </ul>
+            </Show>
+            <textarea
+              class="form-control"
+              name="body"
+              placeholder=placeholder
+              rows="3"
+              prop:value=body
+              on:input=move |ev| set_body.set(event_target_value(&ev))
+            ></textarea>
+          </div>
+          <div class="card-footer">
+            <img src=image.clone() class="comment-author-img"/>
+            <button class="btn btn-sm btn-primary" disabled=move || add_action.pending().get()>
+              "Post Comment"
+            </button>
+          </div>
+        </div>
+      </ActionForm>
+      {move || pending_body().map(|body| view! {cx,
+        <div class="card">
+          <div class="card-block">
+            <p class="card-text">{body}</p>
+          </div>
+          <div class="card-footer">
+            <img src=image.clone() class="comment-author-img"/>
+            " "
+            <span class="comment-author">{username.clone()}</span>
+            <span class="date-posted">"Posting..."</span>
+          </div>
+        </div>
+      })}
+    }
+}
+
+/// Changes the body of the comment `id`.
+#[component]
+fn CommentEditForm(
+    cx: Scope,
+    slug: String,
+    id: i64,
+    body: String,
+    changed: RwSignal<usize>,
+) -> impl IntoView {
+    let edit_action = create_server_action::<EditComment>(cx);
+    let errors = move || match edit_action.value().get() {
+        Some(Ok(form)) => form.body.errors,
+        _ => vec![],
+    };
+    let failed = move || matches!(edit_action.value().get(), Some(Err(_)));
+    create_effect(cx, move |_| {
+        if let Some(Ok(CommentForm {
+            comment: Some(_), ..
+        })) = edit_action.value().get()
+        {
+            changed.update(|n| *n += 1);
+        }
+    });
+
+    view! {cx,
+      <ActionForm action=edit_action>
+        <input type="hidden" name="slug" value=slug/>
+        <input type="hidden" name="id" value=id.to_string()/>
+        <div class="card-block">
+          <FieldErrors errors=errors/>
+          <Show when=failed fallback=move |_| {}>
+            <ul class="error-messages">
+              <li>"Your comment couldn't be changed. It can only be edited for "
+                {EDIT_WINDOW_MINUTES}" minutes after posting."</li>
+            </ul>
+          </Show>
+          <textarea class="form-control" name="body" rows="3">{body}</textarea>
+          <button class="btn btn-sm btn-primary" disabled=move || edit_action.pending().get()>
+            "Save"
+          </button>
+        </div>
+      </ActionForm>
+    }
+}
+
+/// One comment, indented by its `depth` in the thread.
+#[component]
+fn CommentCard(
+    cx: Scope,
+    slug: String,
+    depth: usize,
+    comment: Comment,
+    viewer: Option<Profile>,
+    can_edit: bool,
+    changed: RwSignal<usize>,
+) -> impl IntoView {
+    let delete_action = create_server_action::<DeleteComment>(cx);
+    create_effect(cx, move |_| {
+        if let Some(Ok(())) = delete_action.value().get() {
+            changed.update(|n| *n += 1);
+        }
+    });
+    let (replying, set_replying) = create_signal(cx, false);
+    let (editing, set_editing) = create_signal(cx, false);
+
+    let is_author = viewer
+        .as_ref()
+        .map(|viewer| viewer.username == comment.author.username)
+        .unwrap_or(false);
+    let can_reply = viewer.is_some() && !comment.deleted && depth + 1 < MAX_DEPTH;
+    let can_delete = is_author && !comment.deleted;
+
+    let id = comment.id;
+    let deleted = comment.deleted;
+    let profile_link = format!("/profile/{}", comment.author.username);
+    let date = format_date(&comment.created_at);
+    let edited = comment.edited && !deleted;
+    let body = comment.body;
+    let content = {
+        let slug = slug.clone();
+        let body = body.clone();
+        move || {
+            if deleted {
+                view! {cx, <p class="card-text text-muted">"[deleted]"</p>}.into_view(cx)
+            } else if editing.get() {
+                view! {cx,
+                  <CommentEditForm slug=slug.clone() id=id body=body.clone() changed=changed/>
+                }
+                .into_view(cx)
+            } else {
+                view! {cx, <p class="card-text">{body.clone()}</p>}.into_view(cx)
+            }
+        }
+    };
+    let delete_slug = slug.clone();
+    let author = (!deleted).then(|| {
+        view! {cx,
+          <span>
+            <A href=profile_link.clone() class="comment-author">
+              <img src=comment.author.image.unwrap_or_default() class="comment-author-img"/>
+            </A>
+            " "
+            <A href=profile_link class="comment-author">{comment.author.username}</A>
+          </span>
+        }
+    });
+
+    view! {cx,
+      <div style=format!("margin-left: {}rem", depth * 2)>
+        <div class="card">
+          <div class="card-block">{content}</div>
+          <div class="card-footer">
+            {author}
+            <span class="date-posted">
+              {date}
+              {edited.then(|| " (edited)")}
+            </span>
+            <span class="mod-options">
+              {can_reply.then(|| view! {cx,
+                <i class="ion-reply" title="Reply" on:click=move |_| set_replying.update(|r| *r = !*r)></i>
+              })}
+              {can_edit.then(|| view! {cx,
+                <i class="ion-edit" title="Edit" on:click=move |_| set_editing.update(|e| *e = !*e)></i>
+              })}
+              {can_delete.then(|| view! {cx,
+                <ActionForm action=delete_action>
+                  <input type="hidden" name="slug" value=delete_slug/>
+                  <input type="hidden" name="id" value=id.to_string()/>
+                  <button class="btn btn-sm btn-link" title="Delete">
+                    <i class="ion-trash-a"></i>
+                  </button>
+                </ActionForm>
+              })}
+            </span>
+          </div>
+        </div>
+        {move || match (replying.get(), viewer.clone()) {
+            (true, Some(viewer)) => view! {cx,
+              <div style="margin-left: 2rem">
+                <CommentEditor slug=slug.clone() parent_id=Some(id) viewer=viewer changed=changed/>
+              </div>
+            }
+            .into_view(cx),
+            _ => ().into_view(cx),
+        }}
+      </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(id: i64, parent_id: Option<i64>) -> Comment {
+        Comment {
+            id,
+            created_at: String::new(),
+            updated_at: String::new(),
+            body: format!("comment {}", id),
+            author: Profile {
+                username: "ann".to_string(),
+                bio: None,
+                image: None,
+                following: false,
+            },
+            parent_id,
+            edited: false,
+            deleted: false,
+        }
+    }
+
+    #[test]
+    fn threads_show_newest_first_and_replies_oldest_first() {
+        // 1
+        // ├── 2
+        // │   └── 5
+        // └── 3
+        // 4
+        // └── 6
+        let comments = vec![
+            comment(6, Some(4)),
+            comment(3, Some(1)),
+            comment(5, Some(2)),
+            comment(1, None),
+            comment(4, None),
+            comment(2, Some(1)),
+        ];
+
+        let order: Vec<(usize, i64)> = threaded(comments)
+            .into_iter()
+            .map(|(depth, comment)| (depth, comment.id))
+            .collect();
+        assert_eq!(order, vec![(0, 4), (1, 6), (0, 1), (1, 2), (2, 5), (1, 3)]);
+    }
+}
@@ -0,0 +1,23 @@
+use leptos::*;
+use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+
+/// Opens the SQLite database at `url` and brings its schema up to date.
+pub async fn connect(url: &str) -> Result<SqlitePool, sqlx::Error> {
+    let pool = SqlitePoolOptions::new().connect(url).await?;
+    sqlx::migrate!().run(&pool).await?;
+    Ok(pool)
+}
+
+/// Fetches the pool registered with `App::app_data` from the current request.
+pub fn pool(cx: Scope) -> Result<SqlitePool, ServerFnError> {
+    use_context::<actix_web::HttpRequest>(cx)
+        .and_then(|req| {
+            req.app_data::<actix_web::web::Data<SqlitePool>>()
+                .map(|pool| pool.get_ref().clone())
+        })
+        .ok_or_else(|| ServerFnError::ServerError("no database pool available".to_string()))
+}
+
+pub fn server_error<E: std::fmt::Display>(e: E) -> ServerFnError {
+    ServerFnError::ServerError(e.to_string())
+}
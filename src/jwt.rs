@@ -0,0 +1,82 @@
+use jsonwebtoken::{
+    decode, encode, errors::ErrorKind, get_current_timestamp, DecodingKey, EncodingKey, Header,
+    Validation,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+pub use jsonwebtoken::errors::Error;
+
+const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60 * 24 * 14);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    /// The id of the user the token was issued to. Unlike their email it
+    /// can't change hands.
+    sub: String,
+    /// The user's token version at the time, see `user::revoke_tokens`.
+    ver: i64,
+    iat: u64,
+    exp: u64,
+}
+
+/// Issues and checks the `Authorization: Token <jwt>` tokens used by API
+/// clients. Registered with `App::app_data` so `CurrentUser` can find it.
+pub struct Tokens {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    ttl: Duration,
+}
+
+impl Tokens {
+    pub fn new(secret: &[u8], ttl: Duration) -> Self {
+        Tokens {
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+            ttl,
+        }
+    }
+
+    /// Reads the signing secret from `JWT_SECRET` and the lifetime in seconds
+    /// from `JWT_TTL_SECONDS`. Panics in PROD when there is no secret. In DEV
+    /// a random one is used, so tokens stop working when the server restarts.
+    pub fn from_env(prod: bool) -> Self {
+        let ttl = std::env::var("JWT_TTL_SECONDS")
+            .ok()
+            .and_then(|secs| secs.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_TTL);
+
+        match std::env::var("JWT_SECRET") {
+            Ok(secret) if !secret.is_empty() => Tokens::new(secret.as_bytes(), ttl),
+            _ if prod => panic!("JWT_SECRET must be set in PROD"),
+            _ => {
+                log::warn!("JWT_SECRET is not set, API tokens won't survive a restart");
+                Tokens::new(&rand::random::<[u8; 32]>(), ttl)
+            }
+        }
+    }
+
+    pub fn issue(&self, user_id: i64, token_version: i64) -> Result<String, Error> {
+        let now = get_current_timestamp();
+        let claims = Claims {
+            sub: user_id.to_string(),
+            ver: token_version,
+            iat: now,
+            exp: now + self.ttl.as_secs(),
+        };
+        encode(&Header::default(), &claims, &self.encoding)
+    }
+
+    /// Checks the signature and expiry of `token`, returning the id of the
+    /// user it was issued to and the token version it was issued under. It's
+    /// up to the caller to check that version is still current.
+    pub fn verify(&self, token: &str) -> Result<(i64, i64), Error> {
+        let claims = decode::<Claims>(token, &self.decoding, &Validation::default())?.claims;
+        let user_id = claims
+            .sub
+            .parse()
+            .map_err(|_| Error::from(ErrorKind::InvalidSubject))?;
+        Ok((user_id, claims.ver))
+    }
+}
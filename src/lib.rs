@@ -1,4 +1,20 @@
+#[cfg(feature = "ssr")]
+pub mod api;
 pub mod app;
+pub mod article;
+pub mod comment;
+#[cfg(feature = "ssr")]
+pub mod db;
+#[cfg(feature = "ssr")]
+pub mod jwt;
+#[cfg(feature = "ssr")]
+pub mod markdown;
+#[cfg(feature = "ssr")]
+pub mod password;
+pub mod profile;
+#[cfg(feature = "ssr")]
+pub mod session;
+pub mod user;
 pub mod validations;
 use cfg_if::cfg_if;
 
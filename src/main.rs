@@ -4,14 +4,25 @@ pub mod validations;
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     use actix_files::Files;
-    use actix_session::{storage::CookieSessionStore, Session, SessionMiddleware};
-    use actix_web::*;
-    use conduit_leptos::app::{self, *};
+    use actix_web::{dev::Service, *};
+    use conduit_leptos::{
+        app::{self, *},
+        session::SessionConfig,
+    };
     use leptos::*;
     use leptos_actix::{generate_route_list, LeptosRoutes};
 
     let conf = get_configuration(None).await.unwrap();
     let addr = conf.leptos_options.site_addr;
+
+    let database_url =
+        std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:conduit.db?mode=rwc".to_string());
+    let pool = conduit_leptos::db::connect(&database_url)
+        .await
+        .expect("could not open the database");
+    let prod = matches!(conf.leptos_options.env, leptos::leptos_config::Env::PROD);
+    let tokens = web::Data::new(conduit_leptos::jwt::Tokens::from_env(prod));
+    let session_config = std::sync::Arc::new(SessionConfig::from_env(prod));
     // Generate the list of routes in your Leptos App
     let routes = generate_route_list(|cx| view! { cx, <App/> });
 
@@ -21,17 +32,20 @@ async fn main() -> std::io::Result<()> {
     HttpServer::new(move || {
         let leptos_options = &conf.leptos_options;
         let site_root = &leptos_options.site_root;
+        let rotating_config = session_config.clone();
 
         App::new()
-            .wrap(
-                // create cookie based session middleware
-                SessionMiddleware::builder(
-                    CookieSessionStore::default(),
-                    cookie::Key::from(&[0; 64]),
-                )
-                .cookie_secure(false)
-                .build(),
-            )
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(tokens.clone())
+            // sessions are kept server side, the cookie only holds their key
+            .wrap(session_config.middleware(pool.clone()))
+            // runs before the session middleware, so cookies sealed with a
+            // previous key are readable by it
+            .wrap_fn(move |mut req, srv| {
+                rotating_config.rotate(&mut req);
+                srv.call(req)
+            })
+            .configure(conduit_leptos::api::configure)
             .route("/api/{tail:.*}", leptos_actix::handle_server_fns())
             .leptos_routes(
                 leptos_options.to_owned(),
@@ -0,0 +1,12 @@
+use pulldown_cmark::{html, Options, Parser};
+
+/// Renders user written Markdown to HTML that is safe to inject into a page.
+/// Raw HTML in the input is allowed through the Markdown parser, so the
+/// output is always run through ammonia to strip scripts, event handler
+/// attributes and `javascript:` links.
+pub fn to_safe_html(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, Parser::new_ext(markdown, options));
+    ammonia::clean(&unsafe_html)
+}
@@ -0,0 +1,155 @@
+use actix_web::web;
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Algorithm, Argon2, Params, Version,
+};
+
+pub use argon2::password_hash::Error;
+
+// Cost parameters for new hashes. Hashes made with anything else are
+// upgraded the next time their owner logs in, so these can be raised freely.
+const MEMORY_COST: u32 = 19 * 1024;
+const TIME_COST: u32 = 2;
+const PARALLELISM: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verification {
+    Invalid,
+    Valid,
+    /// The password matched but the hash uses outdated parameters and should
+    /// be replaced with a fresh one from [`hash`].
+    NeedsRehash,
+}
+
+fn hasher() -> Argon2<'static> {
+    let params = Params::new(MEMORY_COST, TIME_COST, PARALLELISM, None)
+        .expect("argon2 cost parameters are valid");
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+}
+
+/// Hashes `password` with Argon2id and a fresh random salt, returning the PHC
+/// string to store.
+pub fn hash(password: &str) -> Result<String, Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(hasher()
+        .hash_password(password.as_bytes(), &salt)?
+        .to_string())
+}
+
+/// Checks `password` against a stored PHC string. The comparison of the
+/// derived key is constant time.
+pub fn verify(password: &str, stored: &str) -> Verification {
+    let parsed = match PasswordHash::new(stored) {
+        Ok(parsed) => parsed,
+        Err(_) => return Verification::Invalid,
+    };
+
+    // The hash carries its own algorithm and parameters, so this verifies
+    // hashes made with older settings too.
+    if hasher()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_err()
+    {
+        return Verification::Invalid;
+    }
+
+    if needs_rehash(&parsed) {
+        Verification::NeedsRehash
+    } else {
+        Verification::Valid
+    }
+}
+
+/// Burns roughly the same time as [`verify`] so a login for an unknown email
+/// can't be told apart from a wrong password by timing.
+pub fn verify_nothing(password: &str) {
+    let _ = hash(password);
+}
+
+// Argon2 is slow on purpose. Called from a request handler it would hold up
+// every other request on the same async worker, so handlers use these
+// versions, which run it on actix's thread pool for blocking work instead.
+
+/// [`hash`] off the async worker.
+pub async fn hash_off_worker(password: String) -> Result<String, Error> {
+    // The pool only fails to answer when the hashing panicked.
+    web::block(move || hash(&password))
+        .await
+        .unwrap_or(Err(Error::Crypto))
+}
+
+/// [`verify`] off the async worker.
+pub async fn verify_off_worker(password: String, stored: String) -> Verification {
+    web::block(move || verify(&password, &stored))
+        .await
+        .unwrap_or(Verification::Invalid)
+}
+
+/// [`verify_nothing`] off the async worker.
+pub async fn verify_nothing_off_worker(password: String) {
+    let _ = web::block(move || verify_nothing(&password)).await;
+}
+
+fn needs_rehash(hash: &PasswordHash) -> bool {
+    if hash.algorithm != Algorithm::Argon2id.ident() || hash.version != Some(Version::V0x13.into())
+    {
+        return true;
+    }
+
+    match Params::try_from(hash) {
+        Ok(params) => {
+            params.m_cost() != MEMORY_COST
+                || params.t_cost() != TIME_COST
+                || params.p_cost() != PARALLELISM
+        }
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_verify_against_their_password() {
+        let stored = hash("correct horse battery").unwrap();
+
+        assert_eq!(
+            verify("correct horse battery", &stored),
+            Verification::Valid
+        );
+    }
+
+    #[test]
+    fn wrong_passwords_are_invalid() {
+        let stored = hash("correct horse battery").unwrap();
+
+        assert_eq!(
+            verify("correct horse staple", &stored),
+            Verification::Invalid
+        );
+        assert_eq!(
+            verify("correct horse battery", "not a hash"),
+            Verification::Invalid
+        );
+    }
+
+    #[test]
+    fn outdated_hashes_need_rehashing() {
+        let salt = SaltString::generate(&mut OsRng);
+        let cheaper = Params::new(MEMORY_COST / 2, TIME_COST, PARALLELISM, None).unwrap();
+        let stored = Argon2::new(Algorithm::Argon2id, Version::V0x13, cheaper)
+            .hash_password(b"correct horse battery", &salt)
+            .unwrap()
+            .to_string();
+
+        assert_eq!(
+            verify("correct horse battery", &stored),
+            Verification::NeedsRehash
+        );
+        assert_eq!(
+            verify("correct horse staple", &stored),
+            Verification::Invalid
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use crate::{
+    app::{Header, HeaderProps},
+    article::{use_page, ArticleList, ArticleListProps},
+    user::Profile,
+};
+use cfg_if::cfg_if;
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ssr")]
+use crate::{
+    app::{logged_in_user, require_user},
+    db, user,
+};
+
+/// What the profile page needs beyond the profile itself.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileDetail {
+    pub profile: Profile,
+    /// Whether this is the current user's own profile.
+    pub is_self: bool,
+    pub logged_in: bool,
+}
+
+cfg_if! {
+if #[cfg(feature = "ssr")] {
+    use sqlx::SqlitePool;
+
+    async fn find_user(pool: &SqlitePool, username: &str) -> Result<user::UserRow, ServerFnError> {
+        user::find_by_username(pool, username)
+            .await
+            .map_err(db::server_error)?
+            .ok_or_else(|| ServerFnError::ServerError("profile not found".to_string()))
+    }
+}
+}
+
+#[server(GetProfile, "/api")]
+pub async fn get_profile(
+    cx: Scope,
+    username: String,
+) -> Result<Option<ProfileDetail>, ServerFnError> {
+    let pool = db::pool(cx)?;
+    let viewer = logged_in_user(cx).await?;
+    let user = match user::find_by_username(&pool, &username)
+        .await
+        .map_err(db::server_error)?
+    {
+        Some(user) => user,
+        None => return Ok(None),
+    };
+    let is_self = viewer
+        .as_ref()
+        .map(|viewer| viewer.id == user.id)
+        .unwrap_or(false);
+    let viewer_id = viewer.map(|viewer| viewer.id);
+
+    Ok(Some(ProfileDetail {
+        profile: user::profile(&pool, user, viewer_id)
+            .await
+            .map_err(db::server_error)?,
+        is_self,
+        logged_in: viewer_id.is_some(),
+    }))
+}
+
+#[server(FollowUser, "/api")]
+pub async fn follow_user(cx: Scope, username: String) -> Result<Profile, ServerFnError> {
+    let pool = db::pool(cx)?;
+    let follower = require_user(cx).await?;
+    let user = find_user(&pool, &username).await?;
+    if follower.id == user.id {
+        return Err(ServerFnError::ServerError(
+            "you can't follow yourself".to_string(),
+        ));
+    }
+    user::follow(&pool, follower.id, user.id)
+        .await
+        .map_err(db::server_error)?;
+
+    user::profile(&pool, user, Some(follower.id))
+        .await
+        .map_err(db::server_error)
+}
+
+#[server(UnfollowUser, "/api")]
+pub async fn unfollow_user(cx: Scope, username: String) -> Result<Profile, ServerFnError> {
+    let pool = db::pool(cx)?;
+    let follower = require_user(cx).await?;
+    let user = find_user(&pool, &username).await?;
+    user::unfollow(&pool, follower.id, user.id)
+        .await
+        .map_err(db::server_error)?;
+
+    user::profile(&pool, user, Some(follower.id))
+        .await
+        .map_err(db::server_error)
+}
+
+/// `/profile/:username`, or `/profile/:username/favorites` with `favorites`.
+#[component]
+pub fn ProfilePage(cx: Scope, favorites: bool) -> impl IntoView {
+    let params = use_params_map(cx);
+    let username =
+        move || params.with(|params| params.get("username").cloned().unwrap_or_default());
+    let detail = create_resource(cx, username, move |username| get_profile(cx, username));
+
+    view! {cx,
+      <Header/>
+      <div class="profile-page">
+        <Suspense fallback=move || view! {cx, <p>"Loading..."</p>}>
+          {move || detail.read().map(|detail| match detail {
+              Ok(Some(detail)) => view! {cx,
+                <ProfileView detail=detail favorites=favorites/>
+              }
+              .into_view(cx),
+              _ => view! {cx,
+                <div class="container page">
+                  <p>"This user doesn't exist."</p>
+                </div>
+              }
+              .into_view(cx),
+          })}
+        </Suspense>
+      </div>
+    }
+}
+
+#[component]
+fn ProfileView(cx: Scope, detail: ProfileDetail, favorites: bool) -> impl IntoView {
+    let ProfileDetail {
+        profile,
+        is_self,
+        logged_in,
+    } = detail;
+    let username = profile.username.clone();
+    let profile_link = format!("/profile/{}", username);
+
+    let action = if is_self {
+        view! {cx,
+          <A class="btn btn-sm btn-outline-secondary action-btn" href="/settings">
+            <i class="ion-gear-a"></i>" Edit Profile Settings"
+          </A>
+        }
+        .into_view(cx)
+    } else {
+        view! {cx,
+          <FollowButton
+            username=username.clone()
+            following=create_rw_signal(cx, profile.following)
+            logged_in=logged_in
+          />
+        }
+        .into_view(cx)
+    };
+    let page = use_page(cx);
+    let tab_link = if favorites {
+        format!("{}/favorites", profile_link)
+    } else {
+        profile_link.clone()
+    };
+    let articles = {
+        let username = username.clone();
+        move || {
+            let tab_link = tab_link.clone();
+            let page_href = move |page| format!("{}?page={}", tab_link, page);
+            if favorites {
+                view! {cx,
+                  <ArticleList
+                    tag=None
+                    author=None
+                    favorited=Some(username.clone())
+                    feed=false
+                    page=page()
+                    page_href=page_href
+                  />
+                }
+            } else {
+                view! {cx,
+                  <ArticleList
+                    tag=None
+                    author=Some(username.clone())
+                    favorited=None
+                    feed=false
+                    page=page()
+                    page_href=page_href
+                  />
+                }
+            }
+        }
+    };
+
+    view! {cx,
+      <div class="user-info">
+        <div class="container">
+          <div class="row">
+            <div class="col-xs-12 col-md-10 offset-md-1">
+              <img src=profile.image.unwrap_or_default() class="user-img"/>
+              <h4>{username}</h4>
+              <p>{profile.bio.unwrap_or_default()}</p>
+              {action}
+            </div>
+          </div>
+        </div>
+      </div>
+
+      <div class="container">
+        <div class="row">
+          <div class="col-xs-12 col-md-10 offset-md-1">
+            <div class="articles-toggle">
+              <ul class="nav nav-pills outline-active">
+                <li class="nav-item">
+                  <A
+                    class=if favorites { "nav-link" } else { "nav-link active" }
+                    href=profile_link.clone()
+                  >
+                    "My Articles"
+                  </A>
+                </li>
+                <li class="nav-item">
+                  <A
+                    class=if favorites { "nav-link active" } else { "nav-link" }
+                    href=format!("{}/favorites", profile_link)
+                  >
+                    "Favorited Articles"
+                  </A>
+                </li>
+              </ul>
+            </div>
+            {articles}
+          </div>
+        </div>
+      </div>
+    }
+}
+
+/// Follows or unfollows `username`. `following` is shared with every other
+/// button for the same user on the page. Sends logged out visitors to log in
+/// instead.
+#[component]
+pub fn FollowButton(
+    cx: Scope,
+    username: String,
+    following: RwSignal<bool>,
+    logged_in: bool,
+) -> impl IntoView {
+    let follow_action = create_server_action::<FollowUser>(cx);
+    let unfollow_action = create_server_action::<UnfollowUser>(cx);
+    create_effect(cx, move |_| {
+        if let Some(Ok(profile)) = follow_action.value().get() {
+            following.set(profile.following);
+        }
+    });
+    create_effect(cx, move |_| {
+        if let Some(Ok(profile)) = unfollow_action.value().get() {
+            following.set(profile.following);
+        }
+    });
+
+    if !logged_in {
+        return view! {cx,
+          <A class="btn btn-sm btn-outline-secondary action-btn" href="/login">
+            <i class="ion-plus-round"></i>" Follow " {username}
+          </A>
+        }
+        .into_view(cx);
+    }
+
+    let label = username.clone();
+    let pending = move || follow_action.pending().get() || unfollow_action.pending().get();
+    view! {cx,
+      <button
+        class=move || if following.get() {
+            "btn btn-sm btn-secondary action-btn"
+        } else {
+            "btn btn-sm btn-outline-secondary action-btn"
+        }
+        disabled=pending
+        on:click=move |_| {
+            let username = username.clone();
+            if following.get() {
+                unfollow_action.dispatch(UnfollowUser { username });
+            } else {
+                follow_action.dispatch(FollowUser { username });
+            }
+        }
+      >
+        <i class="ion-plus-round"></i>
+        {move || if following.get() { " Unfollow " } else { " Follow " }}
+        {label}
+      </button>
+    }
+    .into_view(cx)
+}
@@ -0,0 +1,380 @@
+use actix_session::{
+    config::{PersistentSession, TtlExtensionPolicy},
+    storage::{LoadError, SaveError, SessionKey, SessionStore, UpdateError},
+    Session, SessionMiddleware,
+};
+use actix_web::{
+    cookie::{time::Duration, Cookie, CookieJar, Key, SameSite},
+    dev::ServiceRequest,
+    http::header::{HeaderValue, COOKIE, USER_AGENT},
+    HttpRequest,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::de::DeserializeOwned;
+use sqlx::SqlitePool;
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub const COOKIE_NAME: &str = "conduit_session";
+
+const DEFAULT_IDLE_TIMEOUT: i64 = 60 * 60 * 24 * 7;
+const DEFAULT_MAX_AGE: i64 = 60 * 60 * 24 * 30;
+
+/// How session cookies are sealed and which attributes they carry.
+///
+/// Keys are read from the environment as base64-encoded 64 byte values:
+/// `SESSION_KEY` for the current key and a comma separated
+/// `SESSION_PREVIOUS_KEYS` for keys that are still accepted when reading, so
+/// a key can be rotated without logging everyone out.
+///
+/// Sessions end after `SESSION_IDLE_TIMEOUT_SECONDS` without a request, and
+/// after `SESSION_MAX_AGE_SECONDS` no matter what.
+pub struct SessionConfig {
+    key: Key,
+    previous_keys: Vec<Key>,
+    secure: bool,
+    idle_timeout: Duration,
+    max_age: Duration,
+}
+
+impl SessionConfig {
+    /// Panics in PROD when `SESSION_KEY` is missing or malformed. In DEV a
+    /// random key is generated instead, so sessions end when the server
+    /// restarts.
+    pub fn from_env(prod: bool) -> Self {
+        let key = match std::env::var("SESSION_KEY") {
+            Ok(encoded) => decode_key(&encoded).expect("SESSION_KEY must be 64 bytes of base64"),
+            Err(_) if prod => panic!("SESSION_KEY must be set in PROD"),
+            Err(_) => {
+                log::warn!("SESSION_KEY is not set, using a random key for this run");
+                Key::generate()
+            }
+        };
+        let previous_keys = std::env::var("SESSION_PREVIOUS_KEYS")
+            .map(|keys| {
+                keys.split(',')
+                    .filter(|encoded| !encoded.trim().is_empty())
+                    .map(|encoded| {
+                        decode_key(encoded)
+                            .expect("SESSION_PREVIOUS_KEYS must be 64 bytes of base64 each")
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        SessionConfig {
+            key,
+            previous_keys,
+            secure: prod,
+            idle_timeout: Duration::seconds(seconds_from_env(
+                "SESSION_IDLE_TIMEOUT_SECONDS",
+                DEFAULT_IDLE_TIMEOUT,
+            )),
+            max_age: Duration::seconds(seconds_from_env(
+                "SESSION_MAX_AGE_SECONDS",
+                DEFAULT_MAX_AGE,
+            )),
+        }
+    }
+
+    pub fn middleware(&self, pool: SqlitePool) -> SessionMiddleware<SqliteSessionStore> {
+        let store = SqliteSessionStore {
+            pool,
+            max_age: self.max_age,
+        };
+        // Every request pushes the expiry out again, which makes the TTL an
+        // idle timeout. It also re-sets the cookie each time, which is what
+        // moves cookies from a previous key onto the current one.
+        let lifecycle = PersistentSession::default()
+            .session_ttl(self.idle_timeout)
+            .session_ttl_extension_policy(TtlExtensionPolicy::OnEveryRequest);
+
+        SessionMiddleware::builder(store, self.key.clone())
+            .cookie_name(COOKIE_NAME.to_string())
+            .cookie_secure(self.secure)
+            .cookie_http_only(true)
+            .cookie_same_site(SameSite::Lax)
+            .session_lifecycle(lifecycle)
+            .build()
+    }
+
+    /// Re-seals a session cookie made with one of the previous keys under the
+    /// current key, so the session middleware that runs after this can read
+    /// it.
+    ///
+    /// Nothing here writes the cookie back. That happens because `middleware`
+    /// extends the session's lifetime on every request, which has the session
+    /// middleware set the cookie again, sealed with the current key. Without
+    /// that policy an old-key cookie would be re-sealed on every request
+    /// until the session changed.
+    pub fn rotate(&self, req: &mut ServiceRequest) {
+        if self.previous_keys.is_empty() {
+            return;
+        }
+
+        // Parse the header by hand: `req.cookies()` caches its result and
+        // the session middleware would then still see the old value.
+        let cookies: Vec<Cookie<'static>> = req
+            .headers()
+            .get_all(COOKIE)
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|pair| Cookie::parse(pair.trim().to_string()).ok())
+            .collect();
+        let sealed = match cookies.iter().find(|c| c.name() == COOKIE_NAME) {
+            Some(cookie) => cookie.clone(),
+            None => return,
+        };
+
+        let mut jar = CookieJar::new();
+        jar.add_original(sealed);
+        if jar.private(&self.key).get(COOKIE_NAME).is_some() {
+            return;
+        }
+        let opened = match self
+            .previous_keys
+            .iter()
+            .find_map(|key| jar.private(key).get(COOKIE_NAME))
+        {
+            Some(cookie) => cookie,
+            None => return,
+        };
+
+        let mut resealed = CookieJar::new();
+        resealed
+            .private_mut(&self.key)
+            .add(Cookie::new(COOKIE_NAME, opened.value().to_string()));
+        let resealed = match resealed.get(COOKIE_NAME) {
+            Some(cookie) => cookie.value().to_string(),
+            None => return,
+        };
+
+        let header = cookies
+            .iter()
+            .map(|cookie| {
+                if cookie.name() == COOKIE_NAME {
+                    format!("{}={}", COOKIE_NAME, resealed)
+                } else {
+                    format!("{}={}", cookie.name(), cookie.value())
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        if let Ok(header) = HeaderValue::from_str(&header) {
+            req.headers_mut().insert(COOKIE, header);
+        }
+    }
+}
+
+fn decode_key(encoded: &str) -> Option<Key> {
+    let bytes = STANDARD.decode(encoded.trim()).ok()?;
+    Key::try_from(bytes.as_slice()).ok()
+}
+
+fn seconds_from_env(name: &str, default: i64) -> i64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Logs the user `user_id` in on `session`. The session gets a new key so one
+/// handed out before login can't be used to ride along afterwards.
+pub fn start(req: &HttpRequest, session: &Session, user_id: i64) -> Result<(), actix_web::Error> {
+    let user_agent = req
+        .headers()
+        .get(USER_AGENT)
+        .and_then(|agent| agent.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    session.renew();
+    session.insert("user_id", user_id)?;
+    session.insert("user_agent", user_agent)?;
+    Ok(())
+}
+
+/// Stores session state in the `sessions` table, so a session can be ended
+/// on the server rather than only by the browser dropping its cookie.
+pub struct SqliteSessionStore {
+    pool: SqlitePool,
+    max_age: Duration,
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Pulls a value out of the session state, where actix-session keeps
+/// everything JSON encoded.
+fn state_value<T: DeserializeOwned>(state: &HashMap<String, String>, key: &str) -> Option<T> {
+    state
+        .get(key)
+        .and_then(|value| serde_json::from_str(value).ok())
+}
+
+#[async_trait::async_trait(?Send)]
+impl SessionStore for SqliteSessionStore {
+    async fn load(
+        &self,
+        session_key: &SessionKey,
+    ) -> Result<Option<HashMap<String, String>>, LoadError> {
+        let now = now();
+        let state: Option<(String,)> = sqlx::query_as(
+            "SELECT state FROM sessions
+             WHERE session_key = ? AND expires_at > ? AND created_at > ?",
+        )
+        .bind(session_key.as_ref())
+        .bind(now)
+        .bind(now - self.max_age.whole_seconds())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| LoadError::Other(e.into()))?;
+
+        state
+            .map(|(state,)| serde_json::from_str(&state))
+            .transpose()
+            .map_err(|e| LoadError::Deserialization(e.into()))
+    }
+
+    async fn save(
+        &self,
+        session_state: HashMap<String, String>,
+        ttl: &Duration,
+    ) -> Result<SessionKey, SaveError> {
+        let now = now();
+        let state = serde_json::to_string(&session_state)
+            .map_err(|e| SaveError::Serialization(e.into()))?;
+        let session_key: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(64)
+            .map(char::from)
+            .collect();
+
+        // Expired sessions are never read again, tidy them up while we're here.
+        sqlx::query("DELETE FROM sessions WHERE expires_at <= ?")
+            .bind(now)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| SaveError::Other(e.into()))?;
+        sqlx::query(
+            "INSERT INTO sessions
+             (session_key, state, user_id, user_agent, created_at, last_seen_at, expires_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&session_key)
+        .bind(state)
+        .bind(state_value::<i64>(&session_state, "user_id"))
+        .bind(state_value::<String>(&session_state, "user_agent"))
+        .bind(now)
+        .bind(now)
+        .bind(now + ttl.whole_seconds())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| SaveError::Other(e.into()))?;
+
+        SessionKey::try_from(session_key).map_err(|e| SaveError::Other(e.into()))
+    }
+
+    async fn update(
+        &self,
+        session_key: SessionKey,
+        session_state: HashMap<String, String>,
+        ttl: &Duration,
+    ) -> Result<SessionKey, UpdateError> {
+        let now = now();
+        let state = serde_json::to_string(&session_state)
+            .map_err(|e| UpdateError::Serialization(e.into()))?;
+        let updated = sqlx::query(
+            "UPDATE sessions
+             SET state = ?, user_id = ?, user_agent = ?, last_seen_at = ?, expires_at = ?
+             WHERE session_key = ? AND expires_at > ?",
+        )
+        .bind(state)
+        .bind(state_value::<i64>(&session_state, "user_id"))
+        .bind(state_value::<String>(&session_state, "user_agent"))
+        .bind(now)
+        .bind(now + ttl.whole_seconds())
+        .bind(session_key.as_ref())
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UpdateError::Other(e.into()))?;
+
+        if updated.rows_affected() == 0 {
+            // The session was ended elsewhere, like by logging out all
+            // devices while this request was running. Its state must not
+            // bring it back, so start over with an empty one.
+            return self.save(HashMap::new(), ttl).await.map_err(|e| match e {
+                SaveError::Serialization(e) => UpdateError::Serialization(e),
+                SaveError::Other(e) => UpdateError::Other(e),
+            });
+        }
+
+        Ok(session_key)
+    }
+
+    async fn update_ttl(&self, session_key: &SessionKey, ttl: &Duration) -> anyhow::Result<()> {
+        let now = now();
+        sqlx::query("UPDATE sessions SET last_seen_at = ?, expires_at = ? WHERE session_key = ?")
+            .bind(now)
+            .bind(now + ttl.whole_seconds())
+            .bind(session_key.as_ref())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, session_key: &SessionKey) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM sessions WHERE session_key = ?")
+            .bind(session_key.as_ref())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+/// A live session as listed on the settings page.
+#[derive(sqlx::FromRow, Debug, Clone)]
+pub struct SessionRow {
+    pub id: i64,
+    pub user_agent: Option<String>,
+    pub created_at: String,
+    pub last_seen_at: String,
+}
+
+pub async fn list_for_user(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<Vec<SessionRow>, sqlx::Error> {
+    sqlx::query_as::<_, SessionRow>(
+        "SELECT id, user_agent,
+                strftime('%Y-%m-%d %H:%M UTC', created_at, 'unixepoch') AS created_at,
+                strftime('%Y-%m-%d %H:%M UTC', last_seen_at, 'unixepoch') AS last_seen_at
+         FROM sessions
+         WHERE user_id = ? AND expires_at > ?
+         ORDER BY last_seen_at DESC",
+    )
+    .bind(user_id)
+    .bind(now())
+    .fetch_all(pool)
+    .await
+}
+
+/// Ends every session belonging to the user `user_id`, on every device.
+pub async fn delete_for_user<'e>(
+    executor: impl sqlx::Executor<'e, Database = sqlx::Sqlite>,
+    user_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM sessions WHERE user_id = ?")
+        .bind(user_id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
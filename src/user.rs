@@ -0,0 +1,285 @@
+use cfg_if::cfg_if;
+use serde::{Deserialize, Serialize};
+
+/// A Conduit user as it is shown to the rest of the app. Never carries the
+/// password hash.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct User {
+    pub email: String,
+    pub username: String,
+    pub bio: Option<String>,
+    pub image: Option<String>,
+}
+
+/// How one user appears to another, as in the RealWorld `profile` object.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub username: String,
+    pub bio: Option<String>,
+    pub image: Option<String>,
+    pub following: bool,
+}
+
+cfg_if! {
+if #[cfg(feature = "ssr")] {
+    use sqlx::SqlitePool;
+
+    #[derive(sqlx::FromRow, Debug, Clone)]
+    pub struct UserRow {
+        pub id: i64,
+        pub email: String,
+        pub username: String,
+        pub bio: Option<String>,
+        pub image: Option<String>,
+        pub password_hash: String,
+        /// Bumped to revoke every API token issued so far.
+        pub token_version: i64,
+    }
+
+    impl From<UserRow> for User {
+        fn from(row: UserRow) -> Self {
+            User {
+                email: row.email,
+                username: row.username,
+                bio: row.bio,
+                image: row.image,
+            }
+        }
+    }
+
+    impl From<UserRow> for Profile {
+        fn from(row: UserRow) -> Self {
+            Profile {
+                username: row.username,
+                bio: row.bio,
+                image: row.image,
+                following: false,
+            }
+        }
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: i64) -> Result<Option<UserRow>, sqlx::Error> {
+        sqlx::query_as::<_, UserRow>(
+            "SELECT id, email, username, bio, image, password_hash, token_version
+             FROM users WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_email(pool: &SqlitePool, email: &str) -> Result<Option<UserRow>, sqlx::Error> {
+        sqlx::query_as::<_, UserRow>(
+            "SELECT id, email, username, bio, image, password_hash, token_version
+             FROM users WHERE email = ?",
+        )
+        .bind(email)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_username(
+        pool: &SqlitePool,
+        username: &str,
+    ) -> Result<Option<UserRow>, sqlx::Error> {
+        sqlx::query_as::<_, UserRow>(
+            "SELECT id, email, username, bio, image, password_hash, token_version
+             FROM users WHERE username = ? COLLATE NOCASE",
+        )
+        .bind(username)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Whether anyone but the user `except` goes by `username`.
+    pub async fn username_taken(
+        pool: &SqlitePool,
+        username: &str,
+        except: Option<i64>,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(find_by_username(pool, username)
+            .await?
+            .is_some_and(|user| Some(user.id) != except))
+    }
+
+    /// Whether anyone but the user `except` signed up with `email`.
+    pub async fn email_taken(
+        pool: &SqlitePool,
+        email: &str,
+        except: Option<i64>,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(find_by_email(pool, email)
+            .await?
+            .is_some_and(|user| Some(user.id) != except))
+    }
+
+    /// The column, `username` or `email`, whose UNIQUE constraint `e` broke,
+    /// if that's what went wrong.
+    pub fn duplicate_column(e: &sqlx::Error) -> Option<&'static str> {
+        let e = e.as_database_error()?;
+        // SQLITE_CONSTRAINT_UNIQUE
+        if e.code().as_deref() != Some("2067") {
+            return None;
+        }
+        ["username", "email"]
+            .into_iter()
+            .find(|column| e.message().ends_with(&format!("users.{}", column)))
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        username: &str,
+        email: &str,
+        password_hash: &str,
+    ) -> Result<UserRow, sqlx::Error> {
+        sqlx::query_as::<_, UserRow>(
+            "INSERT INTO users (username, email, password_hash) VALUES (?, ?, ?)
+             RETURNING id, email, username, bio, image, password_hash, token_version",
+        )
+        .bind(username)
+        .bind(email)
+        .bind(password_hash)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update_password_hash(
+        pool: &SqlitePool,
+        id: i64,
+        password_hash: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
+            .bind(password_hash)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Makes every API token issued to user `id` so far stop working.
+    pub async fn revoke_tokens(pool: &SqlitePool, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE users SET token_version = token_version + 1 WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Replaces the editable details of user `id`. With `sign_out`, as for a
+    /// new password or email, the same transaction also ends all their
+    /// sessions and revokes their API tokens.
+    pub async fn update(
+        pool: &SqlitePool,
+        id: i64,
+        user: &User,
+        password_hash: &str,
+        sign_out: bool,
+    ) -> Result<UserRow, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let saved = sqlx::query_as::<_, UserRow>(
+            "UPDATE users
+             SET email = ?, username = ?, bio = ?, image = ?, password_hash = ?,
+                 token_version = token_version + ?,
+                 updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+             WHERE id = ?
+             RETURNING id, email, username, bio, image, password_hash, token_version",
+        )
+        .bind(&user.email)
+        .bind(&user.username)
+        .bind(&user.bio)
+        .bind(&user.image)
+        .bind(password_hash)
+        .bind(sign_out as i64)
+        .bind(id)
+        .fetch_one(&mut tx)
+        .await?;
+        if sign_out {
+            crate::session::delete_for_user(&mut tx, id).await?;
+        }
+        tx.commit().await?;
+        Ok(saved)
+    }
+
+    pub async fn is_following(
+        pool: &SqlitePool,
+        follower_id: i64,
+        followee_id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let (following,): (bool,) = sqlx::query_as(
+            "SELECT EXISTS (SELECT 1 FROM follows WHERE follower_id = ? AND followee_id = ?)",
+        )
+        .bind(follower_id)
+        .bind(followee_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(following)
+    }
+
+    pub async fn follow(pool: &SqlitePool, follower_id: i64, followee_id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("INSERT OR IGNORE INTO follows (follower_id, followee_id) VALUES (?, ?)")
+            .bind(follower_id)
+            .bind(followee_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn unfollow(pool: &SqlitePool, follower_id: i64, followee_id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM follows WHERE follower_id = ? AND followee_id = ?")
+            .bind(follower_id)
+            .bind(followee_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// How `user` appears to `viewer_id`, or to someone logged out.
+    pub async fn profile(
+        pool: &SqlitePool,
+        user: UserRow,
+        viewer_id: Option<i64>,
+    ) -> Result<Profile, sqlx::Error> {
+        let following = match viewer_id {
+            Some(viewer_id) => is_following(pool, viewer_id, user.id).await?,
+            None => false,
+        };
+        Ok(Profile {
+            following,
+            ..user.into()
+        })
+    }
+
+    /// Looks up the user with `email` and checks `password` against their
+    /// stored hash. Returns `None` for an unknown email or a wrong password
+    /// so callers can't tell the two apart.
+    pub async fn authenticate(
+        pool: &SqlitePool,
+        email: &str,
+        password: &str,
+    ) -> Result<Option<UserRow>, sqlx::Error> {
+        use crate::password::{self, Verification};
+
+        let mut user = match find_by_email(pool, email).await? {
+            Some(user) => user,
+            None => {
+                password::verify_nothing_off_worker(password.to_string()).await;
+                return Ok(None);
+            }
+        };
+
+        let verification =
+            password::verify_off_worker(password.to_string(), user.password_hash.clone()).await;
+        match verification {
+            Verification::Invalid => Ok(None),
+            Verification::Valid => Ok(Some(user)),
+            Verification::NeedsRehash => {
+                if let Ok(password_hash) = password::hash_off_worker(password.to_string()).await {
+                    update_password_hash(pool, user.id, &password_hash).await?;
+                    user.password_hash = password_hash;
+                }
+                Ok(Some(user))
+            }
+        }
+    }
+}
+}
@@ -1,5 +1,20 @@
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::{fmt, future::Future};
+use unicode_segmentation::UnicodeSegmentation;
+use url::Url;
+
+pub use validations_derive::Validate;
+
+/// A form made of `Field`s. `#[derive(Validate)]` implements it, along with a
+/// `from_inputs` constructor that runs each field's checks.
+pub trait Validate {
+    /// Every error on the form, along with the name of its field.
+    fn field_errors(&self) -> Box<dyn Iterator<Item = (&'static str, &FieldError)> + '_>;
+
+    fn is_valid(&self) -> bool {
+        self.field_errors().next().is_none()
+    }
+}
 
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Field<T> {
@@ -15,23 +30,235 @@ impl<T> Field<T> {
         };
         Field { input, errors }
     }
+
+    /// A field that may be left out.
+    pub fn optional(input: Option<T>) -> Self {
+        Field {
+            input,
+            errors: vec![],
+        }
+    }
+
+    /// Runs a check that has to wait on something, like a database lookup to
+    /// see whether a username is taken, and adds the error it returns. Only
+    /// runs while the field has no errors, so input that's already rejected
+    /// isn't looked up.
+    pub async fn check_async<F, Fut, E>(&mut self, check: F) -> Result<(), E>
+    where
+        T: Clone,
+        F: FnOnce(T) -> Fut,
+        Fut: Future<Output = Result<Option<FieldError>, E>>,
+    {
+        if !self.errors.is_empty() {
+            return Ok(());
+        }
+        if let Some(input) = self.input.clone() {
+            if let Some(error) = check(input).await? {
+                self.errors.push(error);
+            }
+        }
+        Ok(())
+    }
+
+    /// Requires one of `options`.
+    pub fn one_of<U>(mut self, options: &[U]) -> Self
+    where
+        T: PartialEq<U>,
+        U: fmt::Display,
+    {
+        if let Some(input) = &self.input {
+            if !options.iter().any(|option| input == option) {
+                let options = options.iter().map(ToString::to_string).collect();
+                self.errors.push(FieldError::NotOneOf(options));
+            }
+        }
+        self
+    }
+
+    /// Requires the same input as the field called `name`, as when a password
+    /// is typed twice.
+    pub fn equals_other_field(mut self, name: &str, other: &Field<T>) -> Self
+    where
+        T: PartialEq,
+    {
+        if self.input.is_some() && self.input != other.input {
+            self.errors.push(FieldError::NotEqualTo(name.to_string()));
+        }
+        self
+    }
+
+    /// Runs a check of the caller's own, which explains what's wrong with the
+    /// input in its `Err`.
+    pub fn custom<F>(mut self, check: F) -> Self
+    where
+        F: FnOnce(&T) -> Result<(), String>,
+    {
+        if let Some(input) = &self.input {
+            if let Err(message) = check(input) {
+                self.errors.push(FieldError::Custom(message));
+            }
+        }
+        self
+    }
+}
+
+macro_rules! impl_bounds {
+    ($($number:ty),*) => {$(
+        impl Field<$number> {
+            pub fn min(mut self, min: $number) -> Self {
+                if let Some(n) = self.input {
+                    if n < min {
+                        self.errors.push(FieldError::Min(min.to_string()));
+                    }
+                }
+                self
+            }
+
+            pub fn max(mut self, max: $number) -> Self {
+                if let Some(n) = self.input {
+                    if n > max {
+                        self.errors.push(FieldError::Max(max.to_string()));
+                    }
+                }
+                self
+            }
+        }
+    )*};
+}
+
+impl_bounds!(i64, f64);
+
+/// What the length checks of a `Field<String>` count.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum LengthUnit {
+    /// What a reader sees as one character, like "é" written as "e" and a
+    /// combining accent, or a flag emoji.
+    Graphemes,
+    /// Unicode scalar values, for limits set by something that counts those,
+    /// like a database column.
+    CodePoints,
+}
+
+impl LengthUnit {
+    pub fn count(self, s: &str) -> usize {
+        match self {
+            LengthUnit::Graphemes => s.graphemes(true).count(),
+            LengthUnit::CodePoints => s.chars().count(),
+        }
+    }
 }
 
 impl Field<String> {
-    pub fn min_length(mut self, min: usize) -> Self {
+    /// Requires at least `min` characters, as a reader would count them:
+    /// grapheme clusters rather than bytes or code points.
+    pub fn min_length(self, min: usize) -> Self {
+        self.min_length_in(LengthUnit::Graphemes, min)
+    }
+
+    pub fn min_length_in(mut self, unit: LengthUnit, min: usize) -> Self {
         if let Some(s) = &self.input {
-            if s.len() < min {
+            if unit.count(s) < min {
                 self.errors.push(FieldError::MinLength(min));
             }
         }
         self
     }
 
+    /// Allows at most `max` grapheme clusters.
+    pub fn max_length(self, max: usize) -> Self {
+        self.max_length_in(LengthUnit::Graphemes, max)
+    }
+
+    pub fn max_length_in(mut self, unit: LengthUnit, max: usize) -> Self {
+        if let Some(s) = &self.input {
+            if unit.count(s) > max {
+                self.errors.push(FieldError::MaxLength(max));
+            }
+        }
+        self
+    }
+
+    /// Requires `min` to `max` grapheme clusters.
+    pub fn length_between(self, min: usize, max: usize) -> Self {
+        self.length_between_in(LengthUnit::Graphemes, min, max)
+    }
+
+    pub fn length_between_in(mut self, unit: LengthUnit, min: usize, max: usize) -> Self {
+        if let Some(s) = &self.input {
+            let length = unit.count(s);
+            if length < min || length > max {
+                self.errors.push(FieldError::LengthBetween(min, max));
+            }
+        }
+        self
+    }
+
+    /// Requires a match for `regex` somewhere in the input. Anchor it with `^`
+    /// and `$` to match all of it. Compile the pattern once, for example in a
+    /// `static` `OnceLock<Regex>`, rather than for every form.
+    ///
+    /// Needs the `regex` feature, which is off by default to keep the regex
+    /// engine out of the WASM bundle.
+    #[cfg(feature = "regex")]
+    pub fn matches_regex(mut self, regex: &regex::Regex) -> Self {
+        if let Some(s) = &self.input {
+            if !regex.is_match(s) {
+                self.errors
+                    .push(FieldError::PatternMismatch(regex.as_str().to_string()));
+            }
+        }
+        self
+    }
+
+    /// Requires an absolute `http` or `https` URL, like a link to an image.
+    pub fn url(mut self) -> Self {
+        if let Some(s) = &self.input {
+            let valid = Url::parse(s)
+                .map(|url| matches!(url.scheme(), "http" | "https") && url.has_host())
+                .unwrap_or(false);
+            if !valid {
+                self.errors.push(FieldError::InvalidUrl);
+            }
+        }
+        self
+    }
+
+    /// Checks for an address the way the HTML `type=email` input does: an
+    /// RFC 5322 dot-atom before the '@', and a domain of letters, digits and
+    /// hyphens after it. Internationalized domains are checked in their ASCII
+    /// (punycode) form, which is also what the length limits apply to.
     pub fn email(mut self) -> Self {
         if let Some(s) = &self.input {
-            // TODO: obviously this needs to be more robust
-            if s.split('@').collect::<Vec<_>>().len() != 2 {
-                self.errors.push(FieldError::InvalidEmail)
+            if let Err(error) = check_email(s) {
+                self.errors.push(error);
+            }
+        }
+        self
+    }
+
+    /// Lowercases the domain of a valid address and spells it in ASCII, so
+    /// one mailbox can't be signed up twice under differently written
+    /// domains. The local part is left alone, since only the receiving server
+    /// knows whether it's case sensitive. Nothing is looked up in DNS.
+    pub fn normalize_email(mut self) -> Self {
+        if let Some(s) = &self.input {
+            if check_email(s).is_ok() {
+                if let Some((local, domain)) = s.rsplit_once('@') {
+                    if let Ok(domain) = ascii_domain(domain) {
+                        self.input = Some(format!("{}@{}", local, domain));
+                    }
+                }
+            }
+        }
+        self
+    }
+
+    /// Treats an empty string like a missing one. Put it after `trim` to
+    /// reject all-whitespace input as well.
+    pub fn not_empty(mut self) -> Self {
+        if let Some(s) = &self.input {
+            if s.is_empty() && !self.errors.contains(&FieldError::Required) {
+                self.errors.push(FieldError::Required);
             }
         }
         self
@@ -45,11 +272,99 @@ impl Field<String> {
     }
 }
 
+/// The longest address that fits in an SMTP path (RFC 5321 4.5.3.1.3).
+const MAX_EMAIL_LENGTH: usize = 254;
+const MAX_LOCAL_PART_LENGTH: usize = 64;
+const MAX_DOMAIN_LENGTH: usize = 253;
+const MAX_LABEL_LENGTH: usize = 63;
+
+fn check_email(address: &str) -> Result<(), FieldError> {
+    // The local part can't hold an '@', so the last one splits the address.
+    let (local, domain) = address.rsplit_once('@').ok_or(FieldError::InvalidEmail)?;
+    if local.is_empty() {
+        return Err(FieldError::EmailLocalPartMissing);
+    }
+    if local.len() > MAX_LOCAL_PART_LENGTH {
+        return Err(FieldError::EmailLocalPartTooLong(MAX_LOCAL_PART_LENGTH));
+    }
+    if !is_dot_atom(local) {
+        return Err(FieldError::EmailLocalPartInvalid);
+    }
+    if domain.is_empty() {
+        return Err(FieldError::EmailDomainMissing);
+    }
+    let domain = ascii_domain(domain)?;
+    if local.len() + 1 + domain.len() > MAX_EMAIL_LENGTH {
+        return Err(FieldError::EmailTooLong(MAX_EMAIL_LENGTH));
+    }
+    Ok(())
+}
+
+/// An RFC 5322 `dot-atom`: runs of `atext` joined by single dots.
+fn is_dot_atom(s: &str) -> bool {
+    s.split('.').all(|atom| {
+        !atom.is_empty()
+            && atom
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c))
+    })
+}
+
+/// `domain` in lowercase ASCII, with internationalized labels in punycode,
+/// if it's a valid host name. ASCII domains go through IDNA too, which
+/// rejects `xn--` labels that aren't valid punycode.
+fn ascii_domain(domain: &str) -> Result<String, FieldError> {
+    let ascii = idna::domain_to_ascii(domain).map_err(|_| FieldError::EmailDomainInvalid)?;
+    if ascii.len() > MAX_DOMAIN_LENGTH {
+        return Err(FieldError::EmailDomainTooLong(MAX_DOMAIN_LENGTH));
+    }
+    let valid = ascii.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= MAX_LABEL_LENGTH
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    });
+    if valid {
+        Ok(ascii)
+    } else {
+        Err(FieldError::EmailDomainInvalid)
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub enum FieldError {
     Required,
     MinLength(usize),
+    MaxLength(usize),
+    LengthBetween(usize, usize),
+    /// The input doesn't match this regular expression.
+    PatternMismatch(String),
+    InvalidUrl,
+    NotOneOf(Vec<String>),
+    /// The input differs from that of the field with this name.
+    NotEqualTo(String),
+    // Numeric bounds are kept formatted, so floats don't stop `FieldError`
+    // from being `Eq`.
+    Min(String),
+    Max(String),
+    /// A `custom` check failed with this message.
+    Custom(String),
+    /// There's no '@' at all.
     InvalidEmail,
+    EmailTooLong(usize),
+    EmailLocalPartMissing,
+    EmailLocalPartTooLong(usize),
+    EmailLocalPartInvalid,
+    EmailDomainMissing,
+    EmailDomainTooLong(usize),
+    EmailDomainInvalid,
+    InvalidCredentials,
+    /// Not the password of the user making the change.
+    WrongPassword,
+    Taken,
 }
 
 impl leptos::IntoView for FieldError {
@@ -66,10 +381,427 @@ impl fmt::Display for FieldError {
             FieldError::MinLength(min) => {
                 format!("This field must be at least {} characters.", min)
             }
+            FieldError::MaxLength(max) => {
+                format!("This field can't be longer than {} characters.", max)
+            }
+            FieldError::LengthBetween(min, max) => {
+                format!("This field must be {} to {} characters.", min, max)
+            }
+            FieldError::PatternMismatch(_) => "This field isn't in the right format.".to_string(),
+            FieldError::InvalidUrl => "This field doesn't look like a web address.".to_string(),
+            FieldError::NotOneOf(options) => {
+                format!("This field must be one of: {}.", options.join(", "))
+            }
+            FieldError::NotEqualTo(name) => format!("This field must match {}.", name),
+            FieldError::Min(min) => format!("This field must be at least {}.", min),
+            FieldError::Max(max) => format!("This field can't be more than {}.", max),
+            FieldError::Custom(message) => message.clone(),
             FieldError::InvalidEmail => {
                 "This field doesn't look like an email address.".to_string()
             }
+            FieldError::EmailTooLong(max) => {
+                format!("Email addresses can't be longer than {} characters.", max)
+            }
+            FieldError::EmailLocalPartMissing => {
+                "This email address is missing the name before the @.".to_string()
+            }
+            FieldError::EmailLocalPartTooLong(max) => format!(
+                "The name before the @ can't be longer than {} characters.",
+                max
+            ),
+            FieldError::EmailLocalPartInvalid => {
+                "The name before the @ has characters or dots that aren't allowed.".to_string()
+            }
+            FieldError::EmailDomainMissing => {
+                "This email address is missing the domain after the @.".to_string()
+            }
+            FieldError::EmailDomainTooLong(max) => {
+                format!("The domain can't be longer than {} characters.", max)
+            }
+            FieldError::EmailDomainInvalid => {
+                "The domain after the @ isn't a valid domain name.".to_string()
+            }
+            FieldError::InvalidCredentials => "The email or password is incorrect.".to_string(),
+            FieldError::WrongPassword => "This isn't your current password.".to_string(),
+            FieldError::Taken => "This field is already taken.".to_string(),
         };
         write!(f, "{}", msg)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lengths_count_characters_not_bytes() {
+        // Ten characters, but thirty bytes of UTF-8.
+        let name = "山田太郎山田太郎山田".to_string();
+        assert_eq!(
+            Field::required(Some(name.clone())).min_length(10).errors,
+            vec![]
+        );
+        assert_eq!(
+            Field::required(Some(name.clone())).min_length(11).errors,
+            vec![FieldError::MinLength(11)]
+        );
+        assert_eq!(
+            Field::required(Some(name.clone())).max_length(10).errors,
+            vec![]
+        );
+        assert_eq!(
+            Field::required(Some(name)).length_between(3, 9).errors,
+            vec![FieldError::LengthBetween(3, 9)]
+        );
+    }
+
+    #[test]
+    fn graphemes_count_combined_characters_once() {
+        // "é" as an "e" and a combining accent, and a flag made of two
+        // regional indicators.
+        let input = "e\u{301}\u{1F1EF}\u{1F1F5}".to_string();
+        assert_eq!(LengthUnit::Graphemes.count(&input), 2);
+        assert_eq!(LengthUnit::CodePoints.count(&input), 4);
+        assert_eq!(
+            Field::required(Some(input.clone())).max_length(2).errors,
+            vec![]
+        );
+        assert_eq!(
+            Field::required(Some(input))
+                .max_length_in(LengthUnit::CodePoints, 2)
+                .errors,
+            vec![FieldError::MaxLength(2)]
+        );
+    }
+
+    #[test]
+    fn code_points_are_opt_in() {
+        let input = "👍🏽👍🏽".to_string();
+        assert_eq!(
+            Field::required(Some(input.clone())).min_length(3).errors,
+            vec![FieldError::MinLength(3)]
+        );
+        assert_eq!(
+            Field::required(Some(input))
+                .min_length_in(LengthUnit::CodePoints, 3)
+                .errors,
+            vec![]
+        );
+    }
+
+    #[derive(Debug, Validate)]
+    struct SignUp {
+        #[validate(required, trim, min_length = 3)]
+        name: Field<String>,
+        #[validate(required, min_length = 8)]
+        password: Field<String>,
+        #[validate(equals_other_field = password)]
+        confirmation: Field<String>,
+        #[validate(min = 13)]
+        age: Field<i64>,
+        #[validate(form)]
+        errors: Vec<FieldError>,
+        note: String,
+    }
+
+    #[test]
+    fn derived_forms_take_inputs_in_field_order() {
+        let form = SignUp::from_inputs(
+            Some(" Ann ".to_string()),
+            Some("password1".to_string()),
+            Some("password1".to_string()),
+            None,
+        );
+        assert_eq!(form.name.input.as_deref(), Some("Ann"));
+        assert_eq!(form.password.input.as_deref(), Some("password1"));
+        assert_eq!(form.confirmation.input.as_deref(), Some("password1"));
+        assert_eq!(form.age.input, None);
+        assert_eq!(form.errors, vec![]);
+        assert_eq!(form.note, "");
+        assert!(form.is_valid());
+    }
+
+    #[test]
+    fn derived_forms_label_errors_with_field_names() {
+        let mut form = SignUp::from_inputs(
+            Some(" a ".to_string()),
+            None,
+            Some("password1".to_string()),
+            Some(12),
+        );
+        form.errors.push(FieldError::Taken);
+
+        assert_eq!(
+            form.field_errors().collect::<Vec<_>>(),
+            vec![
+                ("name", &FieldError::MinLength(3)),
+                ("password", &FieldError::Required),
+                (
+                    "confirmation",
+                    &FieldError::NotEqualTo("password".to_string())
+                ),
+                ("age", &FieldError::Min("13".to_string())),
+                ("errors", &FieldError::Taken),
+            ]
+        );
+        assert!(!form.is_valid());
+    }
+
+    #[test]
+    fn urls_must_be_absolute_http() {
+        let cases = [
+            ("https://example.com/me.png", vec![]),
+            ("http://example.com", vec![]),
+            ("example.com/me.png", vec![FieldError::InvalidUrl]),
+            ("ftp://example.com/me.png", vec![FieldError::InvalidUrl]),
+            ("javascript:alert(1)", vec![FieldError::InvalidUrl]),
+        ];
+        for (input, expected) in cases {
+            let field = Field::required(Some(input.to_string())).url();
+            assert_eq!(field.errors, expected, "{}", input);
+        }
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn patterns_are_matched_anywhere_unless_anchored() {
+        let regex = regex::Regex::new("^[a-z]+$").unwrap();
+        assert_eq!(
+            Field::required(Some("conduit".to_string()))
+                .matches_regex(&regex)
+                .errors,
+            vec![]
+        );
+        assert_eq!(
+            Field::required(Some("Conduit".to_string()))
+                .matches_regex(&regex)
+                .errors,
+            vec![FieldError::PatternMismatch("^[a-z]+$".to_string())]
+        );
+    }
+
+    #[test]
+    fn choices_and_other_fields() {
+        let options = ["draft", "published"];
+        assert_eq!(
+            Field::required(Some("draft".to_string()))
+                .one_of(&options)
+                .errors,
+            vec![]
+        );
+        assert_eq!(
+            Field::required(Some("deleted".to_string()))
+                .one_of(&options)
+                .errors,
+            vec![FieldError::NotOneOf(vec![
+                "draft".to_string(),
+                "published".to_string()
+            ])]
+        );
+
+        let password = Field::required(Some("hunter22".to_string()));
+        assert_eq!(
+            Field::required(Some("hunter22".to_string()))
+                .equals_other_field("password", &password)
+                .errors,
+            vec![]
+        );
+        assert_eq!(
+            Field::required(Some("hunter23".to_string()))
+                .equals_other_field("password", &password)
+                .errors,
+            vec![FieldError::NotEqualTo("password".to_string())]
+        );
+    }
+
+    #[test]
+    fn numeric_bounds() {
+        assert_eq!(Field::required(Some(5)).min(1).max(10).errors, vec![]);
+        assert_eq!(
+            Field::required(Some(0)).min(1).errors,
+            vec![FieldError::Min("1".to_string())]
+        );
+        assert_eq!(
+            Field::required(Some(2.5)).max(2.0).errors,
+            vec![FieldError::Max("2".to_string())]
+        );
+    }
+
+    #[test]
+    fn custom_checks_and_missing_input() {
+        let even = |n: &i64| {
+            if n % 2 == 0 {
+                Ok(())
+            } else {
+                Err("This field must be even.".to_string())
+            }
+        };
+        assert_eq!(Field::required(Some(4)).custom(even).errors, vec![]);
+        assert_eq!(
+            Field::required(Some(3)).custom(even).errors,
+            vec![FieldError::Custom("This field must be even.".to_string())]
+        );
+        // Checks skip missing input: `required` already reported it.
+        assert_eq!(
+            Field::<i64>::required(None).min(1).custom(even).errors,
+            vec![FieldError::Required]
+        );
+        assert_eq!(
+            Field::required(Some(" \t".to_string()))
+                .trim()
+                .not_empty()
+                .errors,
+            vec![FieldError::Required]
+        );
+        assert_eq!(
+            Field::<String>::required(None).not_empty().errors,
+            vec![FieldError::Required]
+        );
+    }
+
+    /// Runs a future that never has to wait, like the checks below.
+    fn ready<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+        match future.as_mut().poll(&mut cx) {
+            std::task::Poll::Ready(output) => output,
+            std::task::Poll::Pending => panic!("the check had to wait"),
+        }
+    }
+
+    #[test]
+    fn async_checks_add_their_error() {
+        let mut field = Field::required(Some("taken".to_string()));
+        let result: Result<(), ()> =
+            ready(field.check_async(|name| async move {
+                Ok((name == "taken").then_some(FieldError::Taken))
+            }));
+        assert_eq!(result, Ok(()));
+        assert_eq!(field.errors, vec![FieldError::Taken]);
+
+        let mut field = Field::required(Some("free".to_string()));
+        let result: Result<(), ()> =
+            ready(field.check_async(|name| async move {
+                Ok((name == "taken").then_some(FieldError::Taken))
+            }));
+        assert_eq!(result, Ok(()));
+        assert_eq!(field.errors, vec![]);
+    }
+
+    #[test]
+    fn async_checks_skip_rejected_and_missing_input() {
+        let mut looked_up = false;
+        let mut field = Field::required(Some("ab".to_string())).min_length(3);
+        let result: Result<(), ()> = ready(field.check_async(|_| {
+            looked_up = true;
+            async { Ok(Some(FieldError::Taken)) }
+        }));
+        assert_eq!(result, Ok(()));
+        assert!(!looked_up);
+        assert_eq!(field.errors, vec![FieldError::MinLength(3)]);
+
+        let mut field = Field::<String>::optional(None);
+        let result: Result<(), ()> = ready(field.check_async(|_| {
+            looked_up = true;
+            async { Ok(Some(FieldError::Taken)) }
+        }));
+        assert_eq!(result, Ok(()));
+        assert!(!looked_up);
+        assert_eq!(field.errors, vec![]);
+    }
+
+    #[test]
+    fn async_check_failures_are_returned() {
+        let mut field = Field::required(Some("name".to_string()));
+        let result = ready(field.check_async(|_| async { Err::<Option<FieldError>, _>("down") }));
+        assert_eq!(result, Err("down"));
+        assert_eq!(field.errors, vec![]);
+    }
+
+    #[test]
+    fn email_addresses() {
+        let local = "a".repeat(64);
+        // Four labels of 63, 63, 63 and 61 letters: 253 characters.
+        let domain = [63, 63, 63, 61]
+            .iter()
+            .map(|&n| "a".repeat(n))
+            .collect::<Vec<_>>()
+            .join(".");
+        let cases = [
+            ("a@b.c", None),
+            ("first.last+tag@example.com", None),
+            ("no-at-sign", Some(FieldError::InvalidEmail)),
+            ("@", Some(FieldError::EmailLocalPartMissing)),
+            ("@example.com", Some(FieldError::EmailLocalPartMissing)),
+            ("a@", Some(FieldError::EmailDomainMissing)),
+            ("a@b@", Some(FieldError::EmailLocalPartInvalid)),
+            (".a@b.c", Some(FieldError::EmailLocalPartInvalid)),
+            ("a.@b.c", Some(FieldError::EmailLocalPartInvalid)),
+            ("a..b@c.d", Some(FieldError::EmailLocalPartInvalid)),
+            ("a@b..c", Some(FieldError::EmailDomainInvalid)),
+            ("a@-b.c", Some(FieldError::EmailDomainInvalid)),
+            ("a@b_c.d", Some(FieldError::EmailDomainInvalid)),
+            ("user@bücher.example", None),
+            ("user@xn--bcher-kva.example", None),
+            ("user@xn--a.example", Some(FieldError::EmailDomainInvalid)),
+        ];
+        for (address, expected) in cases {
+            assert_eq!(check_email(address).err(), expected, "{}", address);
+        }
+
+        let too_long = [
+            (format!("{}@b.c", local), None),
+            (
+                format!("{}a@b.c", local),
+                Some(FieldError::EmailLocalPartTooLong(64)),
+            ),
+            (format!("a@{}", &domain[1..]), None),
+            (
+                format!("ab@{}", &domain[1..]),
+                Some(FieldError::EmailTooLong(254)),
+            ),
+            (format!("a@{}", domain), Some(FieldError::EmailTooLong(254))),
+            (
+                format!("a@a{}", domain),
+                Some(FieldError::EmailDomainTooLong(253)),
+            ),
+        ];
+        for (address, expected) in too_long {
+            assert_eq!(check_email(&address).err(), expected, "{}", address);
+        }
+    }
+
+    #[test]
+    fn normalized_email_domains_are_lowercase_ascii() {
+        let normalize = |input: &str| {
+            Field::required(Some(input.to_string()))
+                .normalize_email()
+                .input
+                .unwrap()
+        };
+        assert_eq!(
+            normalize("First.Last@Example.COM"),
+            "First.Last@example.com"
+        );
+        assert_eq!(
+            normalize("user@Bücher.example"),
+            "user@xn--bcher-kva.example"
+        );
+        assert_eq!(
+            normalize("user@XN--BCHER-KVA.example"),
+            "user@xn--bcher-kva.example"
+        );
+        // Invalid addresses are left as they were typed.
+        assert_eq!(normalize("User@Ex..ample"), "User@Ex..ample");
+    }
+
+    #[test]
+    fn trimmed_multibyte_whitespace_does_not_count() {
+        // U+3000 is the ideographic space.
+        let input = "\u{3000}ab\u{3000}".to_string();
+        assert_eq!(
+            Field::required(Some(input)).trim().min_length(3).errors,
+            vec![FieldError::MinLength(3)]
+        );
+    }
+}
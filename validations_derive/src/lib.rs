@@ -0,0 +1,229 @@
+//! `#[derive(Validate)]` for the form structs of `conduit_leptos`, built on
+//! `validations::Field`. The generated code names `crate::validations`, so
+//! the derive only works inside that crate.
+//!
+//! Every `Field<T>` of the form carries a `#[validate(...)]` attribute listing
+//! its checks in the order they run:
+//!
+//! ```ignore
+//! #[derive(Validate)]
+//! pub struct RegisterForm {
+//!     #[validate(required, trim, length_between(3, 20))]
+//!     pub username: Field<String>,
+//!     #[validate(required, trim, min_length = 10)]
+//!     pub password: Field<String>,
+//!     #[validate(required, equals_other_field = password)]
+//!     pub password_confirmation: Field<String>,
+//!     /// Errors that belong to the form as a whole rather than a single field.
+//!     #[validate(form)]
+//!     pub errors: Vec<FieldError>,
+//! }
+//! ```
+//!
+//! `required` starts the field with `Field::required`, otherwise it starts
+//! with `Field::optional`. Any other `name`, `name = value` or
+//! `name(value, ...)` calls the `Field` method of that name with those
+//! arguments. `equals_other_field = other` compares with a field declared
+//! further up. `#[validate(form)]` marks a `Vec<FieldError>` of form wide
+//! errors. Fields without the attribute start out as `Default::default()`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    token, Data, DeriveInput, Error, Expr, Fields, GenericArgument, Ident, PathArguments, Token,
+    Type,
+};
+
+#[proc_macro_derive(Validate, attributes(validate))]
+pub fn derive_validate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// What `#[validate]` says about one field of the form.
+enum Rules {
+    /// A `Field<T>`, and the checks to run on it.
+    Field {
+        input: Box<Type>,
+        required: bool,
+        checks: Vec<TokenStream2>,
+    },
+    /// Errors of the form as a whole.
+    Form,
+    /// Not validated.
+    None,
+}
+
+/// One entry of `#[validate(...)]`: `name`, `name = value` or
+/// `name(value, ...)`.
+struct Check {
+    name: Ident,
+    args: Vec<Expr>,
+}
+
+impl Parse for Check {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let args = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            vec![input.parse()?]
+        } else if input.peek(token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            Punctuated::<Expr, Token![,]>::parse_terminated(&content)?
+                .into_iter()
+                .collect()
+        } else {
+            vec![]
+        };
+        Ok(Check { name, args })
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new(name.span(), "Validate needs named fields")),
+        },
+        _ => return Err(Error::new(name.span(), "Validate only works on structs")),
+    };
+
+    let mut params = vec![];
+    let mut validated = vec![];
+    let mut inits = vec![];
+    let mut errors = vec![];
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named fields have names");
+        let label = ident.to_string();
+        match rules(field)? {
+            Rules::Field {
+                input,
+                required,
+                checks,
+            } => {
+                let start = if required {
+                    quote!(required)
+                } else {
+                    quote!(optional)
+                };
+                params.push(quote!(#ident: ::std::option::Option<#input>));
+                validated.push(
+                    quote!(let #ident = crate::validations::Field::#start(#ident) #(.#checks)*;),
+                );
+                inits.push(quote!(#ident));
+                errors.push(quote!(self.#ident.errors.iter().map(|error| (#label, error))));
+            }
+            Rules::Form => {
+                inits.push(quote!(#ident: ::std::vec::Vec::new()));
+                errors.push(quote!(self.#ident.iter().map(|error| (#label, error))));
+            }
+            Rules::None => inits.push(quote!(#ident: ::std::default::Default::default())),
+        }
+    }
+
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Validates one raw input for each `#[validate]` field, in the
+            /// order the fields are declared.
+            #[allow(clippy::too_many_arguments)]
+            pub fn from_inputs(#(#params),*) -> Self {
+                #(#validated)*
+                #name { #(#inits),* }
+            }
+        }
+
+        impl #impl_generics crate::validations::Validate for #name #ty_generics #where_clause {
+            fn field_errors(
+                &self,
+            ) -> ::std::boxed::Box<
+                dyn ::std::iter::Iterator<Item = (&'static str, &crate::validations::FieldError)> + '_,
+            > {
+                ::std::boxed::Box::new(::std::iter::empty() #(.chain(#errors))*)
+            }
+        }
+    })
+}
+
+fn rules(field: &syn::Field) -> syn::Result<Rules> {
+    let attr = match field
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("validate"))
+    {
+        Some(attr) => attr,
+        None => return Ok(Rules::None),
+    };
+    let parsed: Vec<Check> = if attr.tokens.is_empty() {
+        vec![]
+    } else {
+        attr.parse_args_with(Punctuated::<Check, Token![,]>::parse_terminated)?
+            .into_iter()
+            .collect()
+    };
+
+    let count = parsed.len();
+    let mut required = false;
+    let mut checks = vec![];
+    for Check { name, args } in parsed {
+        if name == "form" {
+            if count > 1 || !args.is_empty() {
+                return Err(Error::new(
+                    name.span(),
+                    "`form` can't be combined with checks",
+                ));
+            }
+            return Ok(Rules::Form);
+        } else if name == "required" {
+            if !args.is_empty() {
+                return Err(Error::new(name.span(), "`required` takes no value"));
+            }
+            required = true;
+        } else if name == "equals_other_field" {
+            let other = match args.as_slice() {
+                [Expr::Path(path)] => path.path.get_ident().cloned(),
+                _ => None,
+            }
+            .ok_or_else(|| Error::new(name.span(), "expected `equals_other_field = field`"))?;
+            let label = other.to_string();
+            checks.push(quote!(#name(#label, &#other)));
+        } else {
+            checks.push(quote!(#name(#(#args),*)));
+        }
+    }
+
+    Ok(Rules::Field {
+        input: Box::new(field_input(&field.ty)?),
+        required,
+        checks,
+    })
+}
+
+/// The `T` of a `Field<T>`.
+fn field_input(ty: &Type) -> syn::Result<Type> {
+    if let Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            if segment.ident == "Field" {
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(GenericArgument::Type(input)) = args.args.first() {
+                        return Ok(input.clone());
+                    }
+                }
+            }
+        }
+    }
+    Err(Error::new(
+        ty.span(),
+        "#[validate] fields must be a `Field<T>`",
+    ))
+}
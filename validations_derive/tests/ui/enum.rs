@@ -0,0 +1,9 @@
+use validations_derive::Validate;
+
+#[derive(Validate)]
+enum Choice {
+    Yes,
+    No,
+}
+
+fn main() {}
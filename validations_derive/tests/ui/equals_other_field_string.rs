@@ -0,0 +1,13 @@
+use validations_derive::Validate;
+
+struct Field<T>(Option<T>);
+
+#[derive(Validate)]
+struct Register {
+    #[validate(required)]
+    password: Field<String>,
+    #[validate(equals_other_field = "password")]
+    confirmation: Field<String>,
+}
+
+fn main() {}
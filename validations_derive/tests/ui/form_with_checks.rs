@@ -0,0 +1,9 @@
+use validations_derive::Validate;
+
+#[derive(Validate)]
+struct Login {
+    #[validate(form, required)]
+    errors: Vec<String>,
+}
+
+fn main() {}
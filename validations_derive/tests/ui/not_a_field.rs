@@ -0,0 +1,9 @@
+use validations_derive::Validate;
+
+#[derive(Validate)]
+struct Login {
+    #[validate(required, trim)]
+    email: String,
+}
+
+fn main() {}
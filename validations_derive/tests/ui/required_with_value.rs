@@ -0,0 +1,11 @@
+use validations_derive::Validate;
+
+struct Field<T>(Option<T>);
+
+#[derive(Validate)]
+struct Login {
+    #[validate(required = true)]
+    email: Field<String>,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use validations_derive::Validate;
+
+struct Field<T>(Option<T>);
+
+#[derive(Validate)]
+struct Login(Field<String>);
+
+fn main() {}